@@ -0,0 +1,112 @@
+// Generated from src/lib/ffi.rs by tests/header.rs, do not edit.
+
+#ifndef EBIN_H
+#define EBIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// C interface for firmware, built with the `ffi` feature. include/ebin.h is
+// generated from this file by tests/header.rs. Quaternions are passed as 4
+// int32 per sample (w, x, y, z in Q27), accel as 3 int16 per sample.
+// Pointers must be valid for the given number of samples; handles must come
+// from the matching _new/decode call and are freed exactly once. Functions
+// returning int32_t return EBIN_OK or a negative error code. EBIN_ERR_PANIC
+// reports a bug in the library instead of unwinding into C, the handle
+// should only be freed after it.
+
+#define EBIN_OK (0)
+#define EBIN_ERR_NULL (-1)
+#define EBIN_ERR_FINISHED (-2)
+#define EBIN_ERR_BAD_MAGIC (-3)
+#define EBIN_ERR_UNSUPPORTED_VERSION (-4)
+#define EBIN_ERR_UNSUPPORTED_REVISION (-5)
+#define EBIN_ERR_UNKNOWN_BLOCK (-6)
+#define EBIN_ERR_TRUNCATED (-7)
+#define EBIN_ERR_MISSING_SETUP (-8)
+#define EBIN_ERR_CORRUPT_BLOCK (-9)
+#define EBIN_ERR_BLOCK_TOO_LARGE (-10)
+#define EBIN_ERR_INVALID_ORIENTATION (-11)
+#define EBIN_ERR_INVALID_QP (-12)
+#define EBIN_ERR_PANIC (-13)
+#define EBIN_ERR_QUAT_OUT_OF_RANGE (-14)
+
+// Encoder handle. Output is collected internally and read out with
+// ebin_encoder_read while recording.
+typedef struct EbinEncoder EbinEncoder;
+
+// NULL if qp is above 31
+EbinEncoder *ebin_encoder_new(uint8_t qp);
+
+void ebin_encoder_free(EbinEncoder *enc);
+
+// emit a keyframe before every n-th gyro data block, 0 disables
+int32_t ebin_encoder_set_keyframe_interval(EbinEncoder *enc, size_t n);
+
+// nonzero makes ebin_encoder_gyro_data return EBIN_ERR_QUAT_OUT_OF_RANGE,
+// writing nothing, for quaternions the quantizer would overflow on
+int32_t ebin_encoder_set_checked(EbinEncoder *enc, int32_t checked);
+
+// gyro algorithm revision of the following blocks, 1 (default) or 2
+int32_t ebin_encoder_set_gyro_revision(EbinEncoder *enc, uint8_t revision);
+
+// `quats` holds 4 * count values
+int32_t ebin_encoder_gyro_data(EbinEncoder *enc, const int32_t *quats, size_t count);
+
+int32_t ebin_encoder_accel_setup(EbinEncoder *enc, uint8_t block_size, uint8_t range);
+
+// `accels` holds 3 * count values
+int32_t ebin_encoder_accel_data(EbinEncoder *enc, const int16_t *accels, size_t count);
+
+int32_t ebin_encoder_time(EbinEncoder *enc, uint32_t dt_us);
+
+int32_t ebin_encoder_time_offset(EbinEncoder *enc, int32_t offset_us);
+
+int32_t ebin_encoder_host_time(EbinEncoder *enc, int64_t host_us);
+
+// `axes` points to 3 characters in Gyroflow notation, e.g. "XYZ"
+int32_t ebin_encoder_imu_orientation(EbinEncoder *enc, const uint8_t *axes);
+
+int32_t ebin_encoder_keyframe(EbinEncoder *enc);
+
+// ends the log, only ebin_encoder_pending/read/free may follow
+int32_t ebin_encoder_finish(EbinEncoder *enc);
+
+// number of bytes ebin_encoder_read can return right now
+size_t ebin_encoder_pending(EbinEncoder *enc);
+
+// moves up to `cap` bytes of finished output to `out`, returns the count
+size_t ebin_encoder_read(EbinEncoder *enc, uint8_t *out, size_t cap);
+
+// Decoded log handle
+typedef struct EbinLog EbinLog;
+
+// decodes a whole file, `*log` is set on success
+int32_t ebin_decode(const uint8_t *buf, size_t len, EbinLog **log);
+
+void ebin_log_free(EbinLog *log);
+
+size_t ebin_log_quat_count(const EbinLog *log);
+
+// copies up to `count` quaternions (4 * count values), returns the number copied
+size_t ebin_log_quats(const EbinLog *log, int32_t *out, size_t count);
+
+size_t ebin_log_quats_f32(const EbinLog *log, float *out, size_t count);
+
+size_t ebin_log_accel_count(const EbinLog *log);
+
+// copies up to `count` accel samples (3 * count values), returns the number copied
+size_t ebin_log_accels(const EbinLog *log, int16_t *out, size_t count);
+
+// sample times of the quaternions in us, 0 if the log has no timing
+size_t ebin_log_quat_times(const EbinLog *log, int64_t *out, size_t count);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // EBIN_H
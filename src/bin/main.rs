@@ -1,113 +1,424 @@
 use std::{
-    fs::File,
-    io::{self, BufRead, Read},
+    collections::BTreeMap,
+    error::Error,
+    fs::{self, File},
+    path::Path,
+    process::ExitCode,
 };
 
 use ebin::{
-    compress::{compress_block, decompress_block},
-    quant::{QuantResult, State},
-    quat::{Fix, Quat, RVec},
+    csv,
+    esplog::{self, DecodeOptions, Writer},
+    gcsv::{self, Gcsv},
+    metadata::{self, Metadata, Value},
+    metrics,
+    quat::Quat,
+    rawquat, sweep, version,
 };
 
-fn parse_gcsv(path: String) -> Option<Vec<RVec>> {
-    let tscale = 0.00180;
-    let gscale = 0.00053263221;
-    let file = File::open(path).map(|x| Some(x)).unwrap_or(None)?;
-    let lines = io::BufReader::new(file).lines();
-    Some(
-        lines
-            .skip_while(|x| {
-                !x.as_ref()
-                    .unwrap()
-                    .chars()
-                    .nth(0)
-                    .unwrap_or('?')
-                    .is_digit(10)
-            })
-            .map(|line| {
-                line.unwrap()
-                    .split(",")
-                    .skip(1)
-                    .take(3)
-                    .map(|x| x.parse::<i32>().unwrap() as f32 * gscale * tscale)
-                    .map(Fix::from_float)
-                    .collect::<Vec<_>>()
-            })
-            .map(|v| RVec::new(v[0], v[1], v[2]))
-            .collect(),
-    )
+const USAGE: &str = "usage:
+  ebin encode <input.gcsv|input.rawquat> <output> [--qp N] [--block N]
+              [--keyframes N] [--rate HZ] [--revision N]
+  ebin decode <input> <output.gcsv|output.csv|output.rawquat>
+              [--rates FILE] [--camera-frame] [--threads N]
+  ebin info <input> [--blocks]
+  ebin verify <input.gcsv|input.rawquat> [--qp N] [--block N] [--blocks]
+  ebin sweep <input.gcsv|input.rawquat> [--qp N,N,..] [--block N,N,..]
+             [--output FILE.csv]";
+
+type Res<T> = Result<T, Box<dyn Error>>;
+
+struct Args {
+    positional: Vec<String>,
+    options: BTreeMap<String, Option<String>>,
+}
+
+impl Args {
+    fn parse(args: &[String], flags: &[&str]) -> Res<Args> {
+        let mut positional = vec![];
+        let mut options = BTreeMap::new();
+        let mut it = args.iter();
+        while let Some(a) = it.next() {
+            if let Some(name) = a.strip_prefix("--") {
+                let value = if flags.contains(&name) {
+                    None
+                } else {
+                    Some(
+                        it.next()
+                            .ok_or(format!("missing value for --{}", name))?
+                            .clone(),
+                    )
+                };
+                options.insert(name.to_owned(), value);
+            } else {
+                positional.push(a.clone());
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn input(&self, i: usize) -> Res<&str> {
+        Ok(self
+            .positional
+            .get(i)
+            .ok_or(format!("missing argument\n{}", USAGE))?)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn get<T: std::str::FromStr>(&self, name: &str, default: T) -> Res<T> {
+        match self.options.get(name) {
+            Some(Some(v)) => Ok(v.parse().map_err(|_| format!("invalid --{} {}", name, v))?),
+            _ => Ok(default),
+        }
+    }
+
+    fn get_opt<T: std::str::FromStr>(&self, name: &str) -> Res<Option<T>> {
+        match self.options.get(name) {
+            Some(Some(v)) => Ok(Some(
+                v.parse().map_err(|_| format!("invalid --{} {}", name, v))?,
+            )),
+            _ => Ok(None),
+        }
+    }
 }
 
-fn parse_gcsv_q(path: String) -> Option<Vec<Quat>> {
-    let rvs = parse_gcsv(path)?;
-    let mut a = Quat::default();
-    Some(
-        rvs.iter()
-            .map(|x| {
-                a = a * Quat::from_rvec(x);
-                a
-            })
-            .collect(),
-    )
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|x| x.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
 }
 
-fn load_raw_q(path: String) -> Option<Vec<Quat>> {
-    let mut file = File::open(path).map(|x| Some(x)).unwrap_or(None)?;
-    let mut buf = vec![];
-    file.read_to_end(&mut buf).map(|x| Some(x)).unwrap_or(None)?;
-    let quats = unsafe {
-        std::slice::from_raw_parts(
-            buf.as_ptr() as *const Quat,
-            buf.len() / std::mem::size_of::<Quat>(),
-        )
+// orientation stream plus whatever else the input carries
+struct Input {
+    quats: Vec<Quat>,
+    gcsv: Option<Gcsv>,
+}
+
+fn load_input(path: &str) -> Res<Input> {
+    if extension(path) == "gcsv" {
+        let gcsv = gcsv::read_file(path)?;
+        Ok(Input {
+            quats: gcsv.to_quats(),
+            gcsv: Some(gcsv),
+        })
+    } else {
+        Ok(Input {
+            quats: rawquat::read_file(path)?,
+            gcsv: None,
+        })
+    }
+}
+
+// largest accel block that evenly divides the gyro block
+fn accel_block_size(block: usize) -> usize {
+    (1..=255).rev().find(|&d| block.is_multiple_of(d)).unwrap()
+}
+
+fn encode(input: &Input, args: &Args) -> Res<Vec<u8>> {
+    let qp: u8 = args.get("qp", 14)?;
+    let block: usize = args.get("block", 512)?;
+    let rate: Option<f64> = args.get_opt("rate")?;
+    if block == 0 || block > u16::MAX as usize {
+        return Err(format!("invalid block size {}", block).into());
+    }
+
+    let mut w = Writer::new(qp)?;
+    w.set_keyframe_interval(args.get("keyframes", 0)?);
+    w.set_gyro_revision(args.get("revision", version::LATEST_GYRO_REVISION)?)?;
+
+    let mut meta = Metadata::new();
+    let mut t_us: Option<Vec<i64>> = None;
+    let mut accels: Option<Vec<[i16; 3]>> = None;
+    if let Some(g) = &input.gcsv {
+        let h = &g.header;
+        meta.set(metadata::GYRO_SCALE, Value::Float(h.gscale));
+        for (k, m) in [
+            ("fwversion", metadata::FIRMWARE_VERSION),
+            ("lensprofile", metadata::LENS_PROFILE),
+            ("vendor", metadata::DEVICE_MODEL),
+        ] {
+            if let Some((_, v)) = h.extra.iter().find(|(x, _)| x == k) {
+                meta.set(m, Value::Str(v.clone()));
+            }
+        }
+        if let Some(o) = h.orientation {
+            w.imu_orientation(o);
+        }
+        t_us = Some(
+            g.samples
+                .iter()
+                .map(|s| (s.t * 1e6).round() as i64)
+                .collect(),
+        );
+
+        // store the raw accel units, the scale goes into the metadata
+        if let (Some(ascale), true) = (h.ascale, g.samples.iter().all(|s| s.accel.is_some())) {
+            let raw = g
+                .samples
+                .iter()
+                .map(|s| {
+                    s.accel.unwrap().map(|a| {
+                        i16::try_from((a / ascale).round() as i64)
+                            .map_err(|_| "accel value does not fit into 16 bits")
+                    })
+                })
+                .map(|[x, y, z]| Ok([x?, y?, z?]))
+                .collect::<Result<Vec<_>, &str>>()?;
+            let range = (ascale * 32768.0).log2().ceil().clamp(0.0, 255.0) as u8;
+            meta.set(metadata::ACCEL_SCALE, Value::Float(ascale));
+            w.accel_setup(accel_block_size(block) as u8, range);
+            accels = Some(raw);
+        }
+    }
+    if let Some(t) = &t_us {
+        if t.len() > 1 {
+            let rate = (t.len() - 1) as f64 * 1e6 / (t[t.len() - 1] - t[0]) as f64;
+            meta.set(metadata::SAMPLE_RATE, Value::Float(rate));
+        }
+    } else if let Some(rate) = rate {
+        meta.set(metadata::SAMPLE_RATE, Value::Float(rate));
+        t_us = Some(
+            (0..input.quats.len())
+                .map(|i| ((i + 1) as f64 * 1e6 / rate).round() as i64)
+                .collect(),
+        );
+    }
+    if !meta.is_empty() {
+        w.metadata(&meta)?;
+    }
+
+    // time blocks close every gyro block, the first span starts one sample
+    // period before the first sample
+    let mut prev_t = t_us.as_ref().filter(|t| !t.is_empty()).map(|t| {
+        let period = if t.len() > 1 { t[1] - t[0] } else { 0 };
+        t[0] - period
+    });
+    if let Some(start) = prev_t {
+        if start != 0 {
+            w.time_offset(i32::try_from(start).map_err(|_| "time offset out of range")?);
+        }
+    }
+
+    let accel_block = accel_block_size(block);
+    for (i, chunk) in input.quats.chunks(block).enumerate() {
+        w.gyro_data(chunk)?;
+        if let Some(accels) = &accels {
+            for a in accels[i * block..i * block + chunk.len()].chunks(accel_block) {
+                w.accel_data(a)?;
+            }
+        }
+        if let (Some(t), Some(prev)) = (&t_us, prev_t.as_mut()) {
+            let cur = t[i * block + chunk.len() - 1];
+            w.time(u32::try_from(cur - *prev).map_err(|_| "non-monotonic timestamps")?);
+            *prev = cur;
+        }
+    }
+    Ok(w.finish())
+}
+
+fn cmd_encode(args: &[String]) -> Res<()> {
+    let args = Args::parse(args, &[])?;
+    let input = load_input(args.input(0)?)?;
+    let out = encode(&input, &args)?;
+    fs::write(args.input(1)?, &out)?;
+    println!(
+        "{} samples, {} bytes, {:.3} bits/sample",
+        input.quats.len(),
+        out.len(),
+        out.len() as f64 * 8.0 / input.quats.len().max(1) as f64
+    );
+    Ok(())
+}
+
+fn cmd_decode(args: &[String]) -> Res<()> {
+    let args = Args::parse(args, &["camera-frame"])?;
+    let buf = fs::read(args.input(0)?)?;
+    let opts = DecodeOptions {
+        threads: args.get("threads", 1)?,
+        camera_frame: args.flag("camera-frame"),
     };
-    Some(Vec::from_iter(quats.iter().map(|x| x.to_owned())))
+    let log = esplog::decode_with(&buf, &opts)?;
+
+    let output = args.input(1)?;
+    match extension(output).as_str() {
+        "gcsv" => Gcsv::from_log(&log)?.write_file(output)?,
+        "csv" => csv::write_files(&log, output, args.get_opt::<String>("rates")?.as_deref())?,
+        _ => rawquat::write_file(output, &log.quats, false)?,
+    }
+    Ok(())
 }
 
-fn main() {
-    // let quats = parse_gcsv_q("testdata/test.gcsv".to_string()).unwrap();
-    let quats = load_raw_q("testdata/test.rawquat".to_string()).unwrap();
+fn cmd_info(args: &[String]) -> Res<()> {
+    let args = Args::parse(args, &["blocks"])?;
+    let buf = fs::read(args.input(0)?)?;
+    let blocks = esplog::scan(&buf)?;
+    let log = esplog::decode(&buf)?;
 
-    let mut state = State::new();
-    let mut data = vec![0; 8192 * 8192];
-    let mut scratch = vec![0; 8192];
-    let mut bytes_tot = 0;
+    println!("format version: {}", log.format_version as char);
+    if args.flag("blocks") {
+        for b in &blocks {
+            println!(
+                "  {:8} {:#04x} {:16} {} bytes",
+                b.pos,
+                b.id,
+                esplog::block_name(b.id),
+                b.len
+            );
+        }
+    }
+    let mut summary: BTreeMap<u8, (usize, usize)> = BTreeMap::new();
+    for b in &blocks {
+        let e = summary.entry(b.id).or_default();
+        e.0 += 1;
+        e.1 += b.len;
+    }
+    println!("blocks:");
+    for (id, (count, bytes)) in &summary {
+        println!(
+            "  {:#04x} {:16} {:8} blocks {:10} bytes",
+            id,
+            esplog::block_name(*id),
+            count,
+            bytes
+        );
+    }
 
-    for c in quats.chunks_exact(512) {
-        let res = compress_block(&state, &c, 14, &mut data[bytes_tot..], &mut scratch).unwrap();
-        state = res.new_state;
-        bytes_tot += res.bytes_put;
+    println!("gyro samples: {}", log.quats.len());
+    println!("accel samples: {}", log.accels.len());
+    if let Some(o) = log.orientation()? {
+        println!("imu orientation: {}", o);
+    }
+    for (k, v) in log.metadata.iter() {
+        match v {
+            Value::Int(x) => println!("{}: {}", k, x),
+            Value::Float(x) => println!("{}: {}", k, x),
+            Value::Str(x) => println!("{}: {}", k, x),
+        }
     }
 
-    dbg!(bytes_tot);
+    let gyro_bytes = summary.get(&esplog::BLK_GYRO_DATA).map_or(0, |x| x.1);
+    let bits_per_sample = gyro_bytes as f64 * 8.0 / log.quats.len().max(1) as f64;
+    println!("gyro bits/sample: {:.3}", bits_per_sample);
+    if let Some(t) = log.timeline() {
+        let ts = &t.quats;
+        if let (Some(first), Some(last)) = (ts.us.first(), ts.us.last()) {
+            let duration = (last - first) as f64 * 1e-6 + 1.0 / ts.sample_rate;
+            println!("duration: {:.3} s", duration);
+            println!(
+                "sample rate: {:.3} Hz (jitter {:.3} us)",
+                ts.sample_rate, ts.jitter_us
+            );
+            println!(
+                "gyro bitrate: {:.1} bit/s",
+                gyro_bytes as f64 * 8.0 / duration
+            );
+            println!(
+                "total bitrate: {:.1} bit/s",
+                buf.len() as f64 * 8.0 / duration
+            );
+        }
+    }
+    Ok(())
+}
 
-    let mut state = State::new();
-    let mut quats_out = vec![Quat::default(); 8192 * 8192];
-    let mut bytes_read = 0;
-    let mut quats_put = 0;
-    while bytes_read < bytes_tot {
-        let res = decompress_block(
-            &state,
-            &data[bytes_read..],
-            &mut quats_out[quats_put..quats_put + 512],
+fn cmd_verify(args: &[String]) -> Res<()> {
+    let args = Args::parse(args, &["blocks"])?;
+    let input = load_input(args.input(0)?)?;
+    let out = encode(&input, &args)?;
+    let log = esplog::decode(&out)?;
+    if log.quats.len() != input.quats.len() {
+        return Err(format!(
+            "decoded {} samples, expected {}",
+            log.quats.len(),
+            input.quats.len()
         )
-        .unwrap();
-        state = res.new_state;
-        bytes_read += res.bytes_eaten;
-        quats_put += res.quats_put;
+        .into());
     }
 
-    let mut rmse = 0.0;
-    for i in 0..quats_put {
-        let err = (quats[i].conj() * quats_out[i]).to_rvec().norm().to_float() * 180.0 / 3.14;
-        rmse += err * err;
-        if err * err > 0.1 {
-            dbg!(i);
+    let report = metrics::compare(&input.quats, &log.quats, args.get("block", 512)?);
+    let o = &report.overall;
+
+    println!("{} samples, {} bytes", input.quats.len(), out.len());
+    println!(
+        "bits/sample: {:.3}",
+        out.len() as f64 * 8.0 / input.quats.len().max(1) as f64
+    );
+    println!("rmse: {:.6} deg", o.rmse);
+    println!("max error: {:.6} deg at sample {}", o.max, o.max_index);
+    for p in [50.0, 90.0, 99.0, 99.9] {
+        println!("p{}: {:.6} deg", p, report.percentile(p));
+    }
+    println!(
+        "axis rmse: {:.6} {:.6} {:.6} deg",
+        report.axis_rmse[0], report.axis_rmse[1], report.axis_rmse[2]
+    );
+    println!(
+        "axis max: {:.6} {:.6} {:.6} deg",
+        report.axis_max[0], report.axis_max[1], report.axis_max[2]
+    );
+    if args.flag("blocks") {
+        for (i, b) in report.blocks.iter().enumerate() {
+            println!(
+                "  block {:5}: rmse {:.6} max {:.6} deg at sample {}",
+                i, b.rmse, b.max, b.max_index
+            );
         }
     }
-    rmse = (rmse / (quats.len() as f32)).sqrt();
+    Ok(())
+}
+
+fn parse_list<T: std::str::FromStr>(s: &str) -> Res<Vec<T>> {
+    s.split(',')
+        .map(|x| {
+            Ok(x.trim()
+                .parse()
+                .map_err(|_| format!("invalid value {}", x))?)
+        })
+        .collect()
+}
 
-    dbg!(rmse);
-    println!("{} quats in", quats_put);
-    println!("{} bytes out", bytes_read);
+fn cmd_sweep(args: &[String]) -> Res<()> {
+    let args = Args::parse(args, &[])?;
+    let input = load_input(args.input(0)?)?;
+    let qps: Vec<u8> = parse_list(&args.get("qp", "8,9,10,11,12,13,14,15,16".to_owned())?)?;
+    let blocks: Vec<usize> = parse_list(&args.get("block", "128,256,512,1024".to_owned())?)?;
+    if blocks.iter().any(|&b| b == 0 || b > u16::MAX as usize) {
+        return Err("invalid block size".into());
+    }
+
+    let points = sweep::sweep(&input.quats, &qps, &blocks)?;
+    match args.get_opt::<String>("output")? {
+        Some(path) => sweep::write_csv(File::create(path)?, &points)?,
+        None => sweep::write_csv(std::io::stdout().lock(), &points)?,
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let res = match args.first().map(String::as_str) {
+        Some("encode") => cmd_encode(&args[1..]),
+        Some("decode") => cmd_decode(&args[1..]),
+        Some("info") => cmd_info(&args[1..]),
+        Some("verify") => cmd_verify(&args[1..]),
+        Some("sweep") => cmd_sweep(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
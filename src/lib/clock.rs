@@ -0,0 +1,130 @@
+use crate::{esplog::Log, timeline::Timeline};
+
+// Linear model `reference = offset_us + scale * log time` fitted by least
+// squares. Log time is the time block clock, the reference is either the
+// host clock (from host time blocks) or the nominal IMU sample clock.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockModel {
+    pub offset_us: f64,
+    pub scale: f64,
+    pub residual_us: f64, // rms fit error
+}
+
+impl ClockModel {
+    pub fn fit(points: &[(f64, f64)]) -> Option<ClockModel> {
+        let n = points.len() as f64;
+        match points.len() {
+            0 => return None,
+            1 => {
+                return Some(ClockModel {
+                    offset_us: points[0].1 - points[0].0,
+                    scale: 1.0,
+                    residual_us: 0.0,
+                })
+            }
+            _ => {}
+        }
+
+        // center first, absolute times in us are too large for the naive sums
+        let mx = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let my = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mx) * (p.0 - mx)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+        if sxx == 0.0 {
+            return None;
+        }
+        let scale = sxy / sxx;
+        let offset_us = my - scale * mx;
+        let residual_us = (points
+            .iter()
+            .map(|p| {
+                let e = p.1 - (offset_us + scale * p.0);
+                e * e
+            })
+            .sum::<f64>()
+            / n)
+            .sqrt();
+
+        Some(ClockModel {
+            offset_us,
+            scale,
+            residual_us,
+        })
+    }
+
+    // positive if the reference clock runs faster than the log clock
+    pub fn drift_ppm(&self) -> f64 {
+        (self.scale - 1.0) * 1e6
+    }
+
+    pub fn map(&self, t_us: i64) -> i64 {
+        (self.offset_us + self.scale * t_us as f64).round() as i64
+    }
+}
+
+impl Log {
+    // log clock time of every time block, including the global offset
+    fn time_block_us(&self) -> Vec<i64> {
+        let mut t = self.time_offset_us.unwrap_or(0) as i64;
+        self.time
+            .iter()
+            .map(|m| {
+                t += m.dt_us as i64;
+                t
+            })
+            .collect()
+    }
+
+    // host clock as a function of log clock, None without host time blocks
+    pub fn host_clock(&self) -> Option<ClockModel> {
+        let times = self.time_block_us();
+        let start = self.time_offset_us.unwrap_or(0) as i64;
+        let points: Vec<(f64, f64)> = self
+            .host_time
+            .iter()
+            .map(|h| {
+                let t = if h.time == 0 {
+                    start
+                } else {
+                    times[h.time - 1]
+                };
+                (t as f64, h.host_us as f64)
+            })
+            .collect();
+        ClockModel::fit(&points)
+    }
+
+    // Drift of the IMU sample clock against the time block clock, found by
+    // fitting the time blocks against the gyro sample count and comparing
+    // the period with the nominal sample rate from the metadata.
+    pub fn sample_clock_drift_ppm(&self) -> Option<f64> {
+        let nominal_us = 1e6 / self.metadata.sample_rate()?;
+        if self.time.is_empty() {
+            return None;
+        }
+        let start = self.time_offset_us.unwrap_or(0) as f64;
+        let points: Vec<(f64, f64)> = std::iter::once((0.0, start))
+            .chain(
+                self.time
+                    .iter()
+                    .zip(self.time_block_us())
+                    .map(|(m, t)| (m.quats as f64, t as f64)),
+            )
+            .collect();
+        let fit = ClockModel::fit(&points)?;
+        Some((fit.scale / nominal_us - 1.0) * 1e6)
+    }
+}
+
+impl Timeline {
+    // timestamps moved onto the clock described by `clock`
+    pub fn resampled(&self, clock: &ClockModel) -> Timeline {
+        let mut out = self.clone();
+        for ts in [&mut out.quats, &mut out.accels] {
+            ts.us.iter_mut().for_each(|t| *t = clock.map(*t));
+            ts.sample_rate /= clock.scale;
+            ts.jitter_us *= clock.scale;
+        }
+        out
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{quant::State, quat::Quat};
+use crate::{
+    fix32::Precision,
+    quant::{QuantError, QuantResult, State, MAX_QP},
+    quat::Quat,
+};
 
 #[derive(Copy, Clone, Debug)]
 pub struct CompressResult {
@@ -26,8 +30,41 @@ pub fn compress_block(
     data: &mut [u8],
     scratch: &mut [i8],
 ) -> Option<CompressResult> {
-    let quant_result = state.quant_block(quats, qp, scratch)?;
+    compress_block_with(state, quats, qp, Precision::Low, data, scratch)
+}
+
+// gyro revision 0x01 uses Precision::Low, 0x02 Precision::High
+pub fn compress_block_with(
+    state: &State,
+    quats: &[Quat],
+    qp: u8,
+    precision: Precision,
+    data: &mut [u8],
+    scratch: &mut [i8],
+) -> Option<CompressResult> {
+    let quant_result = state.quant_block_with(quats, qp, precision, scratch)?;
+    encode_quanted(quant_result, qp, data, scratch)
+}
+
+// compress_block_with on top of State::quant_block_checked
+pub fn compress_block_checked(
+    state: &State,
+    quats: &[Quat],
+    qp: u8,
+    precision: Precision,
+    data: &mut [u8],
+    scratch: &mut [i8],
+) -> Result<CompressResult, QuantError> {
+    let quant_result = state.quant_block_checked(quats, qp, precision, scratch)?;
+    encode_quanted(quant_result, qp, data, scratch).ok_or(QuantError::OutOfSpace)
+}
 
+fn encode_quanted(
+    quant_result: QuantResult,
+    qp: u8,
+    data: &mut [u8],
+    scratch: &[i8],
+) -> Option<CompressResult> {
     // brute-force method
     // let i_var = (0..16)
     //     .map(|i_var| {
@@ -45,13 +82,12 @@ pub fn compress_block(
     // approximate method
     let var = (scratch[0..quant_result.bytes_put]
         .iter()
-        .map(|&x| ((x as i64) * (x as i64)) as i64)
+        .map(|&x| (x as i64) * (x as i64))
         .sum::<i64>() as f64)
         / (quant_result.bytes_put as f64);
-    dbg!(var);
     let i_var = VAR_TABLE.partition_point(|&x| x < var).min(15);
 
-    let var = VAR_TABLE[i_var as usize];
+    let var = VAR_TABLE[i_var];
     let mdl = LaplaceCdf::new(var, SCALE);
     let rans_result = rans_encode(&scratch[..quant_result.bytes_put], &mut data[2..], &mdl)?;
 
@@ -62,7 +98,6 @@ pub fn compress_block(
         .unwrap_or(0);
     data[0] = qp;
     data[1] = i_var as u8 | (cksum << 5);
-    dbg!(i_var);
 
     Some(CompressResult {
         new_state: quant_result.new_state,
@@ -76,29 +111,46 @@ pub fn decompress_block(
     data: &[u8],
     quats: &mut [Quat],
 ) -> Option<DecompressResult> {
+    decompress_block_with(state, data, quats, Precision::Low)
+}
+
+pub fn decompress_block_with(
+    state: &State,
+    data: &[u8],
+    quats: &mut [Quat],
+    precision: Precision,
+) -> Option<DecompressResult> {
+    // qp, model and the initial rANS state
+    if data.len() < 6 {
+        return None;
+    }
     let qp = data[0];
     let i_var = (data[1] & 0x1f) as usize;
     let cksum = data[1] >> 5;
+    if qp > MAX_QP || i_var >= VAR_TABLE.len() {
+        return None;
+    }
 
     let mdl = LaplaceCdf::new(VAR_TABLE[i_var], SCALE);
 
-    let mut rstate = ((data[2] as u32) << 0)
-        | ((data[3] as u32) << 8)
-        | ((data[4] as u32) << 16)
-        | ((data[5] as u32) << 24);
+    let mut rstate = u32::from_le_bytes([data[2], data[3], data[4], data[5]]);
     let mut bytes_eaten = 6;
+    // the encoder keeps the state in [L, 256 L), larger ones would overflow
+    if !(RANS_BYTE_L..RANS_BYTE_L << 8).contains(&rstate) {
+        return None;
+    }
 
     let mut quats_put = 0;
-    let mut new_state = state.clone();
+    let mut new_state = *state;
     let mut own_cksum = 0;
     let mask = (1 << mdl.scale()) - 1;
     while quats_put < quats.len() {
         let mut s = [0, 0, 0];
-        for i in 0..3 {
+        for s in s.iter_mut() {
             let cum = rstate & mask;
             let sym = mdl.icdf(cum);
-            s[i] = sym as i8;
-            own_cksum = (s[i] as u8).wrapping_add(own_cksum);
+            *s = sym as i8;
+            own_cksum = (*s as u8).wrapping_add(own_cksum);
 
             let start = mdl.cdf(sym);
             let freq = mdl.cdf(sym + 1) - start;
@@ -107,7 +159,6 @@ pub fn decompress_block(
 
             while rstate < RANS_BYTE_L {
                 if bytes_eaten >= data.len() {
-                    dbg!(bytes_eaten);
                     return None;
                 }
                 rstate = (rstate << 8) | data[bytes_eaten] as u32;
@@ -115,9 +166,11 @@ pub fn decompress_block(
             }
         }
 
-        if let Some(q) = new_state.dequant_one(&s, qp) {
+        if !new_state.update_in_range(&s, qp) {
+            return None;
+        }
+        if let Some(q) = new_state.dequant_one_with(&s, qp, precision) {
             if quats_put >= quats.len() {
-                dbg!(quats_put);
                 return None;
             }
             quats[quats_put] = q;
@@ -152,10 +205,7 @@ pub fn rans_encode<T: Cdf>(data: &[i8], out: &mut [u8], mdl: &T) -> Option<usize
         }
         state = ((state / freq) << mdl.scale()) + (state % freq) + start;
     }
-    out[bytes_put + 0] = (state >> 24) as u8;
-    out[bytes_put + 1] = (state >> 16) as u8;
-    out[bytes_put + 2] = (state >> 8) as u8;
-    out[bytes_put + 3] = (state >> 0) as u8;
+    out[bytes_put..bytes_put + 4].copy_from_slice(&state.to_be_bytes());
     bytes_put += 4;
     out[0..bytes_put].reverse();
     Some(bytes_put)
@@ -163,10 +213,7 @@ pub fn rans_encode<T: Cdf>(data: &[i8], out: &mut [u8], mdl: &T) -> Option<usize
 
 // use decompress_block instead
 pub fn rans_decode<T: Cdf>(data: &[u8], out: &mut [i8], mdl: &T) -> Option<usize> {
-    let mut state = ((data[0] as u32) << 0)
-        | ((data[1] as u32) << 8)
-        | ((data[2] as u32) << 16)
-        | ((data[3] as u32) << 24);
+    let mut state = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
     let mut bytes_eaten = 4;
 
     let mask = (1 << mdl.scale()) - 1;
@@ -199,7 +246,6 @@ pub trait Cdf {
 
 #[derive(Copy, Clone)]
 pub struct LaplaceCdf {
-    var: f64,
     b: f64,
     scale: i32,
 }
@@ -207,7 +253,6 @@ pub struct LaplaceCdf {
 impl LaplaceCdf {
     pub fn new(var: f64, scale: i32) -> LaplaceCdf {
         LaplaceCdf {
-            var,
             b: (var / 2.0).sqrt(),
             scale,
         }
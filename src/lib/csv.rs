@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::{
+    esplog::Log,
+    gcsv::{Error, Gcsv},
+};
+
+// Plain float CSV exports of a decoded log. The quaternion file carries the
+// decoded orientation as is (`t,w,x,y,z`, seconds), so stabilisation does not
+// have to integrate a rate which was itself differentiated from it. The rate
+// file (`t,gx,gy,gz`, rad/s) is the same data as the .gcsv export.
+
+pub fn write_quats<W: Write>(w: W, log: &Log) -> Result<(), Error> {
+    let timeline = log.timeline().ok_or(Error::NoTimestamps)?;
+    let mut w = BufWriter::new(w);
+    writeln!(w, "t,w,x,y,z")?;
+    for (t, q) in timeline.quats.us.iter().zip(&log.quats) {
+        writeln!(
+            w,
+            "{:.6},{:.9},{:.9},{:.9},{:.9}",
+            *t as f64 * 1e-6,
+            q.w.to_float(),
+            q.x.to_float(),
+            q.y.to_float(),
+            q.z.to_float()
+        )?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+pub fn write_rates<W: Write>(w: W, log: &Log) -> Result<(), Error> {
+    let gcsv = Gcsv::from_log(log)?;
+    let mut w = BufWriter::new(w);
+    writeln!(w, "t,gx,gy,gz")?;
+    for s in &gcsv.samples {
+        writeln!(
+            w,
+            "{:.6},{:.9},{:.9},{:.9}",
+            s.t, s.gyro[0], s.gyro[1], s.gyro[2]
+        )?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+pub fn write_files<P: AsRef<Path>>(log: &Log, quats: P, rates: Option<P>) -> Result<(), Error> {
+    write_quats(File::create(quats)?, log)?;
+    if let Some(path) = rates {
+        write_rates(File::create(path)?, log)?;
+    }
+    Ok(())
+}
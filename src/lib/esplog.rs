@@ -0,0 +1,666 @@
+use std::fmt::Display;
+
+use crate::{
+    compress::{compress_block_checked, compress_block_with, decompress_block_with},
+    fix32::Precision,
+    metadata::Metadata,
+    orientation::ImuOrientation,
+    quant::{QuantError, State, MAX_QP},
+    quat::{Fix, Quat, RVec},
+    version::{self, LATEST_FORMAT_VERSION, LATEST_GYRO_REVISION},
+};
+
+pub const MAGIC: &[u8; 6] = b"EspLog";
+pub const HEADER_SIZE: usize = 7;
+
+pub const BLK_GYRO_SETUP: u8 = 0x01;
+pub const BLK_TIME: u8 = 0x02;
+pub const BLK_GYRO_DATA: u8 = 0x03;
+pub const BLK_ACCEL_SETUP: u8 = 0x04;
+pub const BLK_ACCEL_DATA: u8 = 0x05;
+pub const BLK_TIME_OFFSET: u8 = 0x06;
+pub const BLK_IMU_ORIENTATION: u8 = 0x07;
+pub const BLK_KEYFRAME: u8 = 0x08;
+pub const BLK_METADATA: u8 = 0x09;
+pub const BLK_HOST_TIME: u8 = 0x0a;
+
+const KEYFRAME_SIZE: usize = 38;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    BadMagic,
+    UnsupportedVersion { version: u8 },
+    UnsupportedRevision { revision: u8 },
+    UnknownBlock { id: u8, pos: usize },
+    Truncated { pos: usize },
+    MissingSetup { pos: usize },
+    CorruptBlock { pos: usize },
+    BlockTooLarge { len: usize },
+    InvalidOrientation { orientation: [u8; 3] },
+    InvalidQp { qp: u8 },
+    QuatOutOfRange { index: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::BadMagic => write!(f, "not an EspLog file"),
+            Error::UnsupportedVersion { version } => {
+                write!(f, "unsupported format version {:#04x}", version)
+            }
+            Error::UnsupportedRevision { revision } => {
+                write!(f, "unsupported gyro algorithm revision {:#04x}", revision)
+            }
+            Error::UnknownBlock { id, pos } => {
+                write!(f, "unknown block id {:#04x} at offset {}", id, pos)
+            }
+            Error::Truncated { pos } => write!(f, "truncated block at offset {}", pos),
+            Error::MissingSetup { pos } => {
+                write!(f, "data block without setup block at offset {}", pos)
+            }
+            Error::CorruptBlock { pos } => write!(f, "corrupt block at offset {}", pos),
+            Error::BlockTooLarge { len } => write!(f, "block of {} samples is too large", len),
+            Error::InvalidOrientation { orientation } => write!(
+                f,
+                "invalid IMU orientation {:?}",
+                String::from_utf8_lossy(orientation)
+            ),
+            Error::InvalidQp { qp } => write!(f, "qp {} is out of range 0..={}", qp, MAX_QP),
+            Error::QuatOutOfRange { index } => {
+                write!(f, "quaternion {} of the block is out of range", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// time block marker, `quats` and `accels` are the sample counts decoded before it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimeMark {
+    pub dt_us: u32,
+    pub quats: usize,
+    pub accels: usize,
+}
+
+// host clock reading, `time` is the number of time blocks before it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HostTimeMark {
+    pub host_us: i64,
+    pub time: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Log {
+    pub format_version: u8,
+    pub quats: Vec<Quat>,
+    pub accels: Vec<[i16; 3]>,
+    pub accel_range: Option<u8>, // full range as 2^p g
+    pub time: Vec<TimeMark>,
+    pub host_time: Vec<HostTimeMark>,
+    pub time_offset_us: Option<i32>,
+    pub imu_orientation: Option<[u8; 3]>,
+    pub metadata: Metadata,
+}
+
+impl Log {
+    // stitches a log decoded from the bytes following this one
+    fn append(&mut self, other: Log) {
+        let (nq, na, nt) = (self.quats.len(), self.accels.len(), self.time.len());
+        self.host_time
+            .extend(other.host_time.iter().map(|h| HostTimeMark {
+                host_us: h.host_us,
+                time: h.time + nt,
+            }));
+        self.time.extend(other.time.iter().map(|t| TimeMark {
+            dt_us: t.dt_us,
+            quats: t.quats + nq,
+            accels: t.accels + na,
+        }));
+        self.quats.extend(other.quats);
+        self.accels.extend(other.accels);
+        self.accel_range = other.accel_range.or(self.accel_range);
+        self.time_offset_us = other.time_offset_us.or(self.time_offset_us);
+        self.imu_orientation = other.imu_orientation.or(self.imu_orientation);
+        self.metadata.merge(other.metadata);
+    }
+
+    pub fn orientation(&self) -> Result<Option<ImuOrientation>, Error> {
+        self.imu_orientation
+            .map(|o| {
+                ImuOrientation::from_bytes(o).ok_or(Error::InvalidOrientation { orientation: o })
+            })
+            .transpose()
+    }
+
+    // remaps all samples with the IMU orientation block, which then becomes
+    // the identity so that applying it twice is harmless
+    pub fn to_camera_frame(&mut self) -> Result<(), Error> {
+        if let Some(o) = self.orientation()? {
+            self.quats.iter_mut().for_each(|q| *q = o.remap_quat(q));
+            self.accels.iter_mut().for_each(|a| *a = o.remap_i16(*a));
+            self.imu_orientation = Some(ImuOrientation::IDENTITY.to_bytes());
+        }
+        Ok(())
+    }
+}
+
+// everything needed to decode a block in the middle of a file
+#[derive(Copy, Clone, Debug, Default)]
+struct Context {
+    blocks: &'static [u8], // block ids valid in this file's format version
+    state: State,
+    gyro_revision: u8,
+    gyro_block_size: usize,
+    accel_block_size: usize,
+    accel_range: u8,
+}
+
+// trigonometry used by each gyro revision, encoder and decoder must agree
+fn gyro_precision(revision: u8) -> Option<Precision> {
+    match revision {
+        0x01 => Some(Precision::Low),
+        0x02 => Some(Precision::High),
+        _ => None,
+    }
+}
+
+pub struct Writer {
+    buf: Vec<u8>,
+    ctx: Context,
+    qp: u8,
+    gyro_revision: u8,
+    scratch: Vec<i8>,
+    keyframe_interval: usize,
+    gyro_blocks: usize,
+    last_keyframe: Option<usize>,
+    checked: bool,
+}
+
+impl Writer {
+    pub fn new(qp: u8) -> Result<Writer, Error> {
+        if qp > MAX_QP {
+            return Err(Error::InvalidQp { qp });
+        }
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.push(LATEST_FORMAT_VERSION);
+        Ok(Writer {
+            buf,
+            ctx: Context::default(),
+            qp,
+            gyro_revision: LATEST_GYRO_REVISION,
+            scratch: Vec::new(),
+            keyframe_interval: 0,
+            gyro_blocks: 0,
+            last_keyframe: None,
+            checked: false,
+        })
+    }
+
+    // emit a keyframe before every n-th gyro data block, 0 disables
+    pub fn set_keyframe_interval(&mut self, n: usize) {
+        self.keyframe_interval = n;
+    }
+
+    // Checked gyro_data rejects blocks with quaternions far from unit length
+    // or too fast for the qp (Error::QuatOutOfRange) and writes nothing for
+    // them, instead of panicking in debug builds and writing garbage in
+    // release builds. It costs a few checks per sample.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    // algorithm revision of the following gyro blocks, 0x02 trades encode
+    // speed for a lower quantization error floor at high qp
+    pub fn set_gyro_revision(&mut self, revision: u8) -> Result<(), Error> {
+        if gyro_precision(revision).is_none() {
+            return Err(Error::UnsupportedRevision { revision });
+        }
+        if revision != self.gyro_revision {
+            self.gyro_revision = revision;
+            // forces a setup block
+            self.ctx.gyro_block_size = 0;
+        }
+        Ok(())
+    }
+
+    pub fn gyro_setup(&mut self, block_size: u16) {
+        self.buf.push(BLK_GYRO_SETUP);
+        self.buf.push(self.gyro_revision);
+        self.buf.extend_from_slice(&block_size.to_le_bytes());
+        self.ctx.gyro_revision = self.gyro_revision;
+        self.ctx.gyro_block_size = block_size as usize;
+    }
+
+    pub fn time(&mut self, dt_us: u32) {
+        self.buf.push(BLK_TIME);
+        self.buf.extend_from_slice(&dt_us.to_le_bytes());
+    }
+
+    // a gyro setup block is inserted whenever the block length changes
+    pub fn gyro_data(&mut self, quats: &[Quat]) -> Result<(), Error> {
+        if quats.is_empty() {
+            return Ok(());
+        }
+        if quats.len() > u16::MAX as usize {
+            return Err(Error::BlockTooLarge { len: quats.len() });
+        }
+        // undone if the checked quantizer rejects the block
+        let (end, ctx, last_keyframe) = (self.buf.len(), self.ctx, self.last_keyframe);
+        if self.keyframe_interval > 0 && self.gyro_blocks.is_multiple_of(self.keyframe_interval) {
+            self.keyframe();
+        }
+        if quats.len() != self.ctx.gyro_block_size {
+            self.gyro_setup(quats.len() as u16);
+        }
+
+        self.buf.push(BLK_GYRO_DATA);
+        let start = self.buf.len();
+        if self.scratch.len() < quats.len() * 6 {
+            self.scratch.resize(quats.len() * 6, 0);
+        }
+        let precision = gyro_precision(self.ctx.gyro_revision).unwrap();
+        loop {
+            // rANS never spends more than two bytes on a symbol
+            self.buf.resize(start + 6 + 2 * self.scratch.len(), 0);
+            let (state, data) = (&self.ctx.state, &mut self.buf[start..]);
+            let res = if self.checked {
+                compress_block_checked(state, quats, self.qp, precision, data, &mut self.scratch)
+            } else {
+                compress_block_with(state, quats, self.qp, precision, data, &mut self.scratch)
+                    .ok_or(QuantError::OutOfSpace)
+            };
+            match res {
+                Ok(res) => {
+                    self.buf.truncate(start + res.bytes_put);
+                    self.ctx.state = res.new_state;
+                    break;
+                }
+                // saturated updates did not fit into scratch
+                Err(QuantError::OutOfSpace) => {
+                    let len = self.scratch.len();
+                    self.scratch.resize(len * 2, 0);
+                }
+                Err(err) => {
+                    self.buf.truncate(end);
+                    self.ctx = ctx;
+                    self.last_keyframe = last_keyframe;
+                    return Err(match err {
+                        QuantError::OutOfRange { index } => Error::QuatOutOfRange { index },
+                        _ => Error::InvalidQp { qp: self.qp },
+                    });
+                }
+            }
+        }
+        self.gyro_blocks += 1;
+        Ok(())
+    }
+
+    pub fn accel_setup(&mut self, block_size: u8, range: u8) {
+        self.buf.push(BLK_ACCEL_SETUP);
+        self.buf.push(block_size);
+        self.buf.push(range);
+        self.ctx.accel_block_size = block_size as usize;
+        self.ctx.accel_range = range;
+    }
+
+    // an accel setup block is inserted whenever the block length changes
+    pub fn accel_data(&mut self, accels: &[[i16; 3]]) -> Result<(), Error> {
+        if accels.is_empty() {
+            return Ok(());
+        }
+        if accels.len() > u8::MAX as usize {
+            return Err(Error::BlockTooLarge { len: accels.len() });
+        }
+        if accels.len() != self.ctx.accel_block_size {
+            self.accel_setup(accels.len() as u8, self.ctx.accel_range);
+        }
+        self.buf.push(BLK_ACCEL_DATA);
+        for a in accels {
+            for c in a {
+                self.buf.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn time_offset(&mut self, offset_us: i32) {
+        self.buf.push(BLK_TIME_OFFSET);
+        self.buf.extend_from_slice(&offset_us.to_le_bytes());
+    }
+
+    // reading of the host (camera) clock at this point of the stream
+    pub fn host_time(&mut self, host_us: i64) {
+        self.buf.push(BLK_HOST_TIME);
+        self.buf.extend_from_slice(&host_us.to_le_bytes());
+    }
+
+    pub fn imu_orientation(&mut self, orientation: ImuOrientation) {
+        self.buf.push(BLK_IMU_ORIENTATION);
+        self.buf.extend_from_slice(&orientation.to_bytes());
+    }
+
+    // split into several blocks if there are more than 255 entries, nothing
+    // is written if an entry does not fit
+    pub fn metadata(&mut self, metadata: &Metadata) -> Result<(), Error> {
+        let start = self.buf.len();
+        for chunk in metadata.entries().chunks(u8::MAX as usize) {
+            self.buf.push(BLK_METADATA);
+            if Metadata::encode_entries(chunk, &mut self.buf).is_none() {
+                self.buf.truncate(start);
+                return Err(Error::BlockTooLarge {
+                    len: metadata.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // snapshot of the decoder context, lets readers start decoding here
+    pub fn keyframe(&mut self) {
+        self.patch_keyframe();
+        self.last_keyframe = Some(self.buf.len());
+
+        let q = self.ctx.state.q;
+        let v = self.ctx.state.v;
+        self.buf.push(BLK_KEYFRAME);
+        self.buf.extend_from_slice(&0u32.to_le_bytes());
+        for x in [q.w, q.x, q.y, q.z, v.x, v.y, v.z] {
+            self.buf.extend_from_slice(&x.to_raw().to_le_bytes());
+        }
+        self.buf
+            .extend_from_slice(&(self.ctx.gyro_block_size as u16).to_le_bytes());
+        self.buf.push(self.ctx.gyro_revision);
+        self.buf.push(self.ctx.accel_block_size as u8);
+        self.buf.push(self.ctx.accel_range);
+    }
+
+    // takes the bytes that will not change any more, for writing a log out
+    // while it is recorded; the last keyframe is held back until its length
+    // is known
+    pub fn drain(&mut self) -> Vec<u8> {
+        let end = self.last_keyframe.unwrap_or(self.buf.len());
+        self.last_keyframe = self.last_keyframe.map(|_| 0);
+        self.buf.drain(..end).collect()
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.patch_keyframe();
+        self.buf
+    }
+
+    // store the distance from the previous keyframe to the end of the buffer
+    fn patch_keyframe(&mut self) {
+        if let Some(kf) = self.last_keyframe {
+            let len = (self.buf.len() - kf - KEYFRAME_SIZE) as u32;
+            self.buf[kf + 1..kf + 5].copy_from_slice(&len.to_le_bytes());
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DecodeOptions {
+    pub threads: usize,     // more than one decodes with decode_parallel
+    pub camera_frame: bool, // output samples rotated by the IMU orientation
+}
+
+pub fn decode_with(buf: &[u8], opts: &DecodeOptions) -> Result<Log, Error> {
+    let mut log = if opts.threads > 1 {
+        decode_parallel(buf, opts.threads)?
+    } else {
+        decode(buf)?
+    };
+
+    if opts.camera_frame {
+        log.to_camera_frame()?;
+    }
+    Ok(log)
+}
+
+pub fn decode(buf: &[u8]) -> Result<Log, Error> {
+    let (mut pos, mut ctx, mut log) = decode_header(buf)?;
+    while pos < buf.len() {
+        pos = decode_block(buf, pos, &mut ctx, &mut log)?;
+    }
+    Ok(log)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlockInfo {
+    pub id: u8,
+    pub pos: usize,
+    pub len: usize, // including the block id
+}
+
+// position and size of every block, gyro blocks have to be decoded for that
+pub fn scan(buf: &[u8]) -> Result<Vec<BlockInfo>, Error> {
+    let (mut pos, mut ctx, mut log) = decode_header(buf)?;
+    let mut blocks = vec![];
+    while pos < buf.len() {
+        let next = decode_block(buf, pos, &mut ctx, &mut log)?;
+        blocks.push(BlockInfo {
+            id: buf[pos],
+            pos,
+            len: next - pos,
+        });
+        pos = next;
+    }
+    Ok(blocks)
+}
+
+pub fn block_name(id: u8) -> &'static str {
+    match id {
+        BLK_GYRO_SETUP => "gyro setup",
+        BLK_TIME => "time",
+        BLK_GYRO_DATA => "gyro data",
+        BLK_ACCEL_SETUP => "accel setup",
+        BLK_ACCEL_DATA => "accel data",
+        BLK_TIME_OFFSET => "time offset",
+        BLK_IMU_ORIENTATION => "imu orientation",
+        BLK_KEYFRAME => "keyframe",
+        BLK_METADATA => "metadata",
+        BLK_HOST_TIME => "host time",
+        _ => "unknown",
+    }
+}
+
+// Splits the file at keyframes and decodes the segments on up to `threads`
+// threads. Blocks before the first keyframe are decoded sequentially, so
+// files without keyframes still decode, just not in parallel.
+pub fn decode_parallel(buf: &[u8], threads: usize) -> Result<Log, Error> {
+    let (mut pos, mut ctx, mut log) = decode_header(buf)?;
+    let blocks = ctx.blocks;
+    while pos < buf.len() && buf[pos] != BLK_KEYFRAME {
+        pos = decode_block(buf, pos, &mut ctx, &mut log)?;
+    }
+
+    let mut segments: Vec<std::ops::Range<usize>> = vec![];
+    while pos < buf.len() {
+        if buf[pos] != BLK_KEYFRAME {
+            return Err(Error::CorruptBlock { pos });
+        }
+        let len = u32::from_le_bytes(read(buf, pos + 1)?) as usize;
+        let end = pos + KEYFRAME_SIZE + len;
+        if end > buf.len() {
+            return Err(Error::Truncated { pos });
+        }
+        segments.push(pos..end);
+        pos = end;
+    }
+    if segments.is_empty() {
+        return Ok(log);
+    }
+
+    let per_thread = segments.len().div_ceil(threads.max(1));
+    let results: Vec<Result<Log, Error>> = std::thread::scope(|s| {
+        let handles: Vec<_> = segments
+            .chunks(per_thread)
+            .map(|chunk| {
+                s.spawn(move || {
+                    let mut ctx = Context {
+                        blocks,
+                        ..Default::default()
+                    };
+                    let mut log = Log::default();
+                    for seg in chunk {
+                        let mut pos = seg.start;
+                        while pos < seg.end {
+                            pos = decode_block(&buf[..seg.end], pos, &mut ctx, &mut log)?;
+                        }
+                    }
+                    Ok(log)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("decoder thread panicked"))
+            .collect()
+    });
+    for res in results {
+        log.append(res?);
+    }
+    Ok(log)
+}
+
+fn decode_header(buf: &[u8]) -> Result<(usize, Context, Log), Error> {
+    if buf.len() < HEADER_SIZE || &buf[..MAGIC.len()] != MAGIC {
+        return Err(Error::BadMagic);
+    }
+    let version = buf[MAGIC.len()];
+    let format = version::format_version(version).ok_or(Error::UnsupportedVersion { version })?;
+    let ctx = Context {
+        blocks: format.blocks,
+        ..Default::default()
+    };
+    let log = Log {
+        format_version: version,
+        ..Default::default()
+    };
+    Ok((HEADER_SIZE, ctx, log))
+}
+
+fn read<const K: usize>(buf: &[u8], pos: usize) -> Result<[u8; K], Error> {
+    buf.get(pos..pos + K)
+        .map(|x| x.try_into().unwrap())
+        .ok_or(Error::Truncated { pos })
+}
+
+fn read_i32(buf: &[u8], pos: usize) -> Result<i32, Error> {
+    Ok(i32::from_le_bytes(read(buf, pos)?))
+}
+
+// decodes the block starting at `pos`, returns the position of the next one
+fn decode_block(buf: &[u8], pos: usize, ctx: &mut Context, log: &mut Log) -> Result<usize, Error> {
+    let id = buf[pos];
+    if !ctx.blocks.contains(&id) {
+        return Err(Error::UnknownBlock { id, pos });
+    }
+    match id {
+        BLK_GYRO_SETUP => {
+            let [revision, lo, hi] = read(buf, pos + 1)?;
+            if version::gyro_revision(revision).is_none() {
+                return Err(Error::UnsupportedRevision { revision });
+            }
+            ctx.gyro_revision = revision;
+            ctx.gyro_block_size = u16::from_le_bytes([lo, hi]) as usize;
+            Ok(pos + 4)
+        }
+        BLK_TIME => {
+            log.time.push(TimeMark {
+                dt_us: u32::from_le_bytes(read(buf, pos + 1)?),
+                quats: log.quats.len(),
+                accels: log.accels.len(),
+            });
+            Ok(pos + 5)
+        }
+        BLK_GYRO_DATA => {
+            if ctx.gyro_block_size == 0 {
+                return Err(Error::MissingSetup { pos });
+            }
+            // qp, model and the initial rANS state
+            read::<6>(buf, pos + 1)?;
+
+            let start = log.quats.len();
+            log.quats
+                .resize(start + ctx.gyro_block_size, Quat::default());
+            let precision =
+                gyro_precision(ctx.gyro_revision).ok_or(Error::UnsupportedRevision {
+                    revision: ctx.gyro_revision,
+                })?;
+            let res = decompress_block_with(
+                &ctx.state,
+                &buf[pos + 1..],
+                &mut log.quats[start..],
+                precision,
+            )
+            .ok_or(Error::CorruptBlock { pos })?;
+            ctx.state = res.new_state;
+            Ok(pos + 1 + res.bytes_eaten)
+        }
+        BLK_ACCEL_SETUP => {
+            let [block_size, range] = read(buf, pos + 1)?;
+            ctx.accel_block_size = block_size as usize;
+            ctx.accel_range = range;
+            log.accel_range = Some(range);
+            Ok(pos + 3)
+        }
+        BLK_ACCEL_DATA => {
+            if ctx.accel_block_size == 0 {
+                return Err(Error::MissingSetup { pos });
+            }
+            let mut apos = pos + 1;
+            for _ in 0..ctx.accel_block_size {
+                let [xl, xh, yl, yh, zl, zh] = read(buf, apos)?;
+                log.accels.push([
+                    i16::from_le_bytes([xl, xh]),
+                    i16::from_le_bytes([yl, yh]),
+                    i16::from_le_bytes([zl, zh]),
+                ]);
+                apos += 6;
+            }
+            Ok(apos)
+        }
+        BLK_TIME_OFFSET => {
+            log.time_offset_us = Some(read_i32(buf, pos + 1)?);
+            Ok(pos + 5)
+        }
+        BLK_IMU_ORIENTATION => {
+            log.imu_orientation = Some(read(buf, pos + 1)?);
+            Ok(pos + 4)
+        }
+        BLK_KEYFRAME => {
+            read::<KEYFRAME_SIZE>(buf, pos)?;
+            let v: Vec<Fix> = (0..7)
+                .map(|i| Fix::from_raw(read_i32(buf, pos + 5 + 4 * i).unwrap()))
+                .collect();
+            ctx.state = State {
+                q: Quat::new(v[0], v[1], v[2], v[3]),
+                v: RVec::new(v[4], v[5], v[6]),
+            };
+            if !ctx.state.in_range() {
+                return Err(Error::CorruptBlock { pos });
+            }
+            ctx.gyro_block_size = u16::from_le_bytes([buf[pos + 33], buf[pos + 34]]) as usize;
+            ctx.gyro_revision = buf[pos + 35];
+            ctx.accel_block_size = buf[pos + 36] as usize;
+            ctx.accel_range = buf[pos + 37];
+            Ok(pos + KEYFRAME_SIZE)
+        }
+        BLK_METADATA => {
+            let (meta, len) =
+                Metadata::decode_entries(&buf[pos + 1..]).ok_or(Error::CorruptBlock { pos })?;
+            log.metadata.merge(meta);
+            Ok(pos + 1 + len)
+        }
+        BLK_HOST_TIME => {
+            log.host_time.push(HostTimeMark {
+                host_us: i64::from_le_bytes(read(buf, pos + 1)?),
+                time: log.time.len(),
+            });
+            Ok(pos + 9)
+        }
+        _ => Err(Error::UnknownBlock { id, pos }),
+    }
+}
@@ -0,0 +1,424 @@
+#![allow(clippy::missing_safety_doc)]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::{
+    esplog::{self, Error, Log, Writer},
+    orientation::ImuOrientation,
+    quat::{Fix, Quat},
+};
+
+// C interface for firmware, built with the `ffi` feature. include/ebin.h is
+// generated from this file by tests/header.rs. Quaternions are passed as 4
+// int32 per sample (w, x, y, z in Q27), accel as 3 int16 per sample.
+// Pointers must be valid for the given number of samples; handles must come
+// from the matching _new/decode call and are freed exactly once. Functions
+// returning int32_t return EBIN_OK or a negative error code. EBIN_ERR_PANIC
+// reports a bug in the library instead of unwinding into C, the handle
+// should only be freed after it.
+
+pub const EBIN_OK: i32 = 0;
+pub const EBIN_ERR_NULL: i32 = -1;
+pub const EBIN_ERR_FINISHED: i32 = -2;
+pub const EBIN_ERR_BAD_MAGIC: i32 = -3;
+pub const EBIN_ERR_UNSUPPORTED_VERSION: i32 = -4;
+pub const EBIN_ERR_UNSUPPORTED_REVISION: i32 = -5;
+pub const EBIN_ERR_UNKNOWN_BLOCK: i32 = -6;
+pub const EBIN_ERR_TRUNCATED: i32 = -7;
+pub const EBIN_ERR_MISSING_SETUP: i32 = -8;
+pub const EBIN_ERR_CORRUPT_BLOCK: i32 = -9;
+pub const EBIN_ERR_BLOCK_TOO_LARGE: i32 = -10;
+pub const EBIN_ERR_INVALID_ORIENTATION: i32 = -11;
+pub const EBIN_ERR_INVALID_QP: i32 = -12;
+pub const EBIN_ERR_PANIC: i32 = -13;
+pub const EBIN_ERR_QUAT_OUT_OF_RANGE: i32 = -14;
+
+fn error_code(e: &Error) -> i32 {
+    match e {
+        Error::BadMagic => EBIN_ERR_BAD_MAGIC,
+        Error::UnsupportedVersion { .. } => EBIN_ERR_UNSUPPORTED_VERSION,
+        Error::UnsupportedRevision { .. } => EBIN_ERR_UNSUPPORTED_REVISION,
+        Error::UnknownBlock { .. } => EBIN_ERR_UNKNOWN_BLOCK,
+        Error::Truncated { .. } => EBIN_ERR_TRUNCATED,
+        Error::MissingSetup { .. } => EBIN_ERR_MISSING_SETUP,
+        Error::CorruptBlock { .. } => EBIN_ERR_CORRUPT_BLOCK,
+        Error::BlockTooLarge { .. } => EBIN_ERR_BLOCK_TOO_LARGE,
+        Error::InvalidOrientation { .. } => EBIN_ERR_INVALID_ORIENTATION,
+        Error::InvalidQp { .. } => EBIN_ERR_INVALID_QP,
+        Error::QuatOutOfRange { .. } => EBIN_ERR_QUAT_OUT_OF_RANGE,
+    }
+}
+
+// runs `f`, returning `fallback` if it panics
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+fn status(r: Result<(), Error>) -> i32 {
+    r.map_or_else(|e| error_code(&e), |_| EBIN_OK)
+}
+
+// Encoder handle. Output is collected internally and read out with
+// ebin_encoder_read while recording.
+pub struct EbinEncoder {
+    writer: Option<Writer>,
+    out: Vec<u8>,
+}
+
+impl EbinEncoder {
+    fn writer(&mut self) -> Result<&mut Writer, i32> {
+        self.writer.as_mut().ok_or(EBIN_ERR_FINISHED)
+    }
+}
+
+// shorthand for the common prologue of the encoder calls
+macro_rules! writer {
+    ($enc:expr) => {
+        match $enc.as_mut() {
+            Some(enc) => match enc.writer() {
+                Ok(w) => w,
+                Err(e) => return e,
+            },
+            None => return EBIN_ERR_NULL,
+        }
+    };
+}
+
+// NULL if qp is above 31
+#[no_mangle]
+pub extern "C" fn ebin_encoder_new(qp: u8) -> *mut EbinEncoder {
+    guard(std::ptr::null_mut(), || match Writer::new(qp) {
+        Ok(w) => Box::into_raw(Box::new(EbinEncoder {
+            writer: Some(w),
+            out: Vec::new(),
+        })),
+        Err(_) => std::ptr::null_mut(),
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_free(enc: *mut EbinEncoder) {
+    guard((), || {
+        if !enc.is_null() {
+            drop(Box::from_raw(enc));
+        }
+    })
+}
+
+// emit a keyframe before every n-th gyro data block, 0 disables
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_set_keyframe_interval(
+    enc: *mut EbinEncoder,
+    n: usize,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).set_keyframe_interval(n);
+        EBIN_OK
+    })
+}
+
+// nonzero makes ebin_encoder_gyro_data return EBIN_ERR_QUAT_OUT_OF_RANGE,
+// writing nothing, for quaternions the quantizer would overflow on
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_set_checked(enc: *mut EbinEncoder, checked: i32) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).set_checked(checked != 0);
+        EBIN_OK
+    })
+}
+
+// gyro algorithm revision of the following blocks, 1 (default) or 2
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_set_gyro_revision(
+    enc: *mut EbinEncoder,
+    revision: u8,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        status(writer!(enc).set_gyro_revision(revision))
+    })
+}
+
+// `quats` holds 4 * count values
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_gyro_data(
+    enc: *mut EbinEncoder,
+    quats: *const i32,
+    count: usize,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        if quats.is_null() && count > 0 {
+            return EBIN_ERR_NULL;
+        }
+        let w = writer!(enc);
+        let raw = if count > 0 {
+            slice::from_raw_parts(quats, 4 * count)
+        } else {
+            &[]
+        };
+        let quats: Vec<Quat> = raw
+            .chunks_exact(4)
+            .map(|c| {
+                let [w, x, y, z] = [c[0], c[1], c[2], c[3]].map(Fix::from_raw);
+                Quat::new(w, x, y, z)
+            })
+            .collect();
+        status(w.gyro_data(&quats))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_accel_setup(
+    enc: *mut EbinEncoder,
+    block_size: u8,
+    range: u8,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).accel_setup(block_size, range);
+        EBIN_OK
+    })
+}
+
+// `accels` holds 3 * count values
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_accel_data(
+    enc: *mut EbinEncoder,
+    accels: *const i16,
+    count: usize,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        if accels.is_null() && count > 0 {
+            return EBIN_ERR_NULL;
+        }
+        let w = writer!(enc);
+        let raw = if count > 0 {
+            slice::from_raw_parts(accels, 3 * count)
+        } else {
+            &[]
+        };
+        let accels: Vec<[i16; 3]> = raw.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        status(w.accel_data(&accels))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_time(enc: *mut EbinEncoder, dt_us: u32) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).time(dt_us);
+        EBIN_OK
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_time_offset(enc: *mut EbinEncoder, offset_us: i32) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).time_offset(offset_us);
+        EBIN_OK
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_host_time(enc: *mut EbinEncoder, host_us: i64) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).host_time(host_us);
+        EBIN_OK
+    })
+}
+
+// `axes` points to 3 characters in Gyroflow notation, e.g. "XYZ"
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_imu_orientation(
+    enc: *mut EbinEncoder,
+    axes: *const u8,
+) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        if axes.is_null() {
+            return EBIN_ERR_NULL;
+        }
+        let w = writer!(enc);
+        let axes = [*axes, *axes.add(1), *axes.add(2)];
+        match ImuOrientation::from_bytes(axes) {
+            Some(o) => {
+                w.imu_orientation(o);
+                EBIN_OK
+            }
+            None => EBIN_ERR_INVALID_ORIENTATION,
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_keyframe(enc: *mut EbinEncoder) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        writer!(enc).keyframe();
+        EBIN_OK
+    })
+}
+
+// ends the log, only ebin_encoder_pending/read/free may follow
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_finish(enc: *mut EbinEncoder) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        let Some(enc) = enc.as_mut() else {
+            return EBIN_ERR_NULL;
+        };
+        match enc.writer.take() {
+            Some(w) => {
+                enc.out.extend_from_slice(&w.finish());
+                EBIN_OK
+            }
+            None => EBIN_ERR_FINISHED,
+        }
+    })
+}
+
+// number of bytes ebin_encoder_read can return right now
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_pending(enc: *mut EbinEncoder) -> usize {
+    guard(0, || {
+        let Some(enc) = enc.as_mut() else {
+            return 0;
+        };
+        if let Some(w) = enc.writer.as_mut() {
+            enc.out.extend_from_slice(&w.drain());
+        }
+        enc.out.len()
+    })
+}
+
+// moves up to `cap` bytes of finished output to `out`, returns the count
+#[no_mangle]
+pub unsafe extern "C" fn ebin_encoder_read(
+    enc: *mut EbinEncoder,
+    out: *mut u8,
+    cap: usize,
+) -> usize {
+    guard(0, || {
+        if out.is_null() {
+            return 0;
+        }
+        let n = ebin_encoder_pending(enc).min(cap);
+        if n > 0 {
+            let enc = &mut *enc;
+            slice::from_raw_parts_mut(out, n).copy_from_slice(&enc.out[..n]);
+            enc.out.drain(..n);
+        }
+        n
+    })
+}
+
+// Decoded log handle
+pub struct EbinLog {
+    log: Log,
+}
+
+// decodes a whole file, `*log` is set on success
+#[no_mangle]
+pub unsafe extern "C" fn ebin_decode(buf: *const u8, len: usize, log: *mut *mut EbinLog) -> i32 {
+    guard(EBIN_ERR_PANIC, || {
+        if buf.is_null() || log.is_null() {
+            return EBIN_ERR_NULL;
+        }
+        match esplog::decode(slice::from_raw_parts(buf, len)) {
+            Ok(l) => {
+                *log = Box::into_raw(Box::new(EbinLog { log: l }));
+                EBIN_OK
+            }
+            Err(e) => error_code(&e),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_free(log: *mut EbinLog) {
+    guard((), || {
+        if !log.is_null() {
+            drop(Box::from_raw(log));
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_quat_count(log: *const EbinLog) -> usize {
+    guard(0, || log.as_ref().map_or(0, |l| l.log.quats.len()))
+}
+
+// copies up to `count` quaternions (4 * count values), returns the number copied
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_quats(log: *const EbinLog, out: *mut i32, count: usize) -> usize {
+    guard(0, || {
+        let (Some(log), false) = (log.as_ref(), out.is_null()) else {
+            return 0;
+        };
+        let n = log.log.quats.len().min(count);
+        let out = slice::from_raw_parts_mut(out, 4 * n);
+        for (o, q) in out.chunks_exact_mut(4).zip(&log.log.quats) {
+            o.copy_from_slice(&[q.w, q.x, q.y, q.z].map(|x| x.to_raw()));
+        }
+        n
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_quats_f32(
+    log: *const EbinLog,
+    out: *mut f32,
+    count: usize,
+) -> usize {
+    guard(0, || {
+        let (Some(log), false) = (log.as_ref(), out.is_null()) else {
+            return 0;
+        };
+        let n = log.log.quats.len().min(count);
+        let out = slice::from_raw_parts_mut(out, 4 * n);
+        for (o, q) in out.chunks_exact_mut(4).zip(&log.log.quats) {
+            o.copy_from_slice(&[q.w, q.x, q.y, q.z].map(|x| x.to_float()));
+        }
+        n
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_accel_count(log: *const EbinLog) -> usize {
+    guard(0, || log.as_ref().map_or(0, |l| l.log.accels.len()))
+}
+
+// copies up to `count` accel samples (3 * count values), returns the number copied
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_accels(
+    log: *const EbinLog,
+    out: *mut i16,
+    count: usize,
+) -> usize {
+    guard(0, || {
+        let (Some(log), false) = (log.as_ref(), out.is_null()) else {
+            return 0;
+        };
+        let n = log.log.accels.len().min(count);
+        let out = slice::from_raw_parts_mut(out, 3 * n);
+        for (o, a) in out.chunks_exact_mut(3).zip(&log.log.accels) {
+            o.copy_from_slice(a);
+        }
+        n
+    })
+}
+
+// sample times of the quaternions in us, 0 if the log has no timing
+#[no_mangle]
+pub unsafe extern "C" fn ebin_log_quat_times(
+    log: *const EbinLog,
+    out: *mut i64,
+    count: usize,
+) -> usize {
+    guard(0, || {
+        let (Some(log), false) = (log.as_ref(), out.is_null()) else {
+            return 0;
+        };
+        let Some(timeline) = log.log.timeline() else {
+            return 0;
+        };
+        let n = timeline.quats.us.len().min(count);
+        slice::from_raw_parts_mut(out, n).copy_from_slice(&timeline.quats.us[..n]);
+        n
+    })
+}
@@ -1,6 +1,22 @@
 use std::fmt::{Debug, Display};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
+use crate::fix64::Fix64;
+
+// Accuracy of sin, cos and atan2. Low is the original approximation used
+// by sin/cos/atan2 (errors up to 4e-4 and 9e-4 rad), Medium evaluates
+// longer series in 32-bit arithmetic (2.5 ulp) and High uses 40 fractional
+// bits (0.6 ulp). These bounds hold for Q27 and are checked in tests/trig.rs.
+// Low atan2 is kept bit-exact, including its sign for y < 0 < x where it
+// returns -atan2(y, x): quat::to_rvec hits that for w < 0 in the codec.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
 #[derive(Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
 pub struct Fix32<const N: usize> {
     v: i32,
@@ -14,6 +30,10 @@ impl<const N: usize> Fix32<N> {
     pub const HALF_PI: Fix32<N> = Self::from_fixed(7244019458077122842i64, 62);
     pub const TWO_PI: Fix32<N> = Self::from_fixed(7244019458077122842i64, 60);
 
+    pub const ZERO: Fix32<N> = Fix32 { v: 0 };
+    pub const MAX: Fix32<N> = Fix32 { v: i32::MAX };
+    pub const MIN: Fix32<N> = Fix32 { v: i32::MIN };
+
     const fn from_fixed(v: i64, s: usize) -> Fix32<N> {
         if s > N {
             Fix32 {
@@ -26,7 +46,7 @@ impl<const N: usize> Fix32<N> {
         }
     }
 
-    pub fn from_raw(x: i32) -> Fix32<N> {
+    pub const fn from_raw(x: i32) -> Fix32<N> {
         Fix32 { v: x }
     }
 
@@ -60,7 +80,7 @@ impl<const N: usize> Fix32<N> {
             let fc = Fix32::from_fixed(9178930894564541004i64, 63);
 
             let xx = x * x;
-            return ((fa * xx + fb) * xx + fc) * x;
+            ((fa * xx + fb) * xx + fc) * x
         }
         fn atan_div<const N: usize>(y: Fix32<N>, x: Fix32<N>) -> Fix32<N> {
             debug_assert!(x.v != 0);
@@ -171,8 +191,397 @@ impl<const N: usize> Fix32<N> {
     pub fn fmod(&self, m: Fix32<N>) -> Fix32<N> {
         Fix32 { v: self.v % m.v }
     }
+
+    // product and quotient before narrowing, rounded half away from zero
+    fn mul_wide(self, rhs: Fix32<N>) -> i64 {
+        let val = (self.v as i64) * (rhs.v as i64) / (Self::MULT as i64 / 2);
+        (val / 2) + (val % 2)
+    }
+
+    fn div_wide(self, rhs: Fix32<N>) -> Option<i64> {
+        if rhs.v == 0 {
+            return None;
+        }
+        let val = (self.v as i64) * (Self::MULT as i64) * 2 / (rhs.v as i64);
+        Some((val / 2) + (val % 2))
+    }
+
+    fn saturate(v: i64) -> Fix32<N> {
+        Fix32 {
+            v: v.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        }
+    }
+
+    pub fn checked_add(self, rhs: Fix32<N>) -> Option<Fix32<N>> {
+        self.v.checked_add(rhs.v).map(Self::from_raw)
+    }
+
+    pub fn checked_sub(self, rhs: Fix32<N>) -> Option<Fix32<N>> {
+        self.v.checked_sub(rhs.v).map(Self::from_raw)
+    }
+
+    pub fn checked_mul(self, rhs: Fix32<N>) -> Option<Fix32<N>> {
+        i32::try_from(self.mul_wide(rhs)).ok().map(Self::from_raw)
+    }
+
+    // None on division by zero as well
+    pub fn checked_div(self, rhs: Fix32<N>) -> Option<Fix32<N>> {
+        let v = self.div_wide(rhs)?;
+        i32::try_from(v).ok().map(Self::from_raw)
+    }
+
+    pub fn checked_neg(self) -> Option<Fix32<N>> {
+        self.v.checked_neg().map(Self::from_raw)
+    }
+
+    pub fn saturating_add(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.v.saturating_add(rhs.v),
+        }
+    }
+
+    pub fn saturating_sub(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.v.saturating_sub(rhs.v),
+        }
+    }
+
+    pub fn saturating_mul(self, rhs: Fix32<N>) -> Fix32<N> {
+        Self::saturate(self.mul_wide(rhs))
+    }
+
+    // division by zero gives MAX or MIN by the sign of self, 0 / 0 gives 0
+    pub fn saturating_div(self, rhs: Fix32<N>) -> Fix32<N> {
+        match self.div_wide(rhs) {
+            Some(v) => Self::saturate(v),
+            None => match self.v.cmp(&0) {
+                std::cmp::Ordering::Less => Self::MIN,
+                std::cmp::Ordering::Equal => Self::ZERO,
+                std::cmp::Ordering::Greater => Self::MAX,
+            },
+        }
+    }
+
+    pub fn saturating_neg(self) -> Fix32<N> {
+        Fix32 {
+            v: self.v.saturating_neg(),
+        }
+    }
+
+    pub fn wrapping_add(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.v.wrapping_add(rhs.v),
+        }
+    }
+
+    pub fn wrapping_sub(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.v.wrapping_sub(rhs.v),
+        }
+    }
+
+    pub fn wrapping_mul(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.mul_wide(rhs) as i32,
+        }
+    }
+
+    // panics on division by zero like i32::wrapping_div
+    pub fn wrapping_div(self, rhs: Fix32<N>) -> Fix32<N> {
+        Fix32 {
+            v: self.div_wide(rhs).expect("division by zero") as i32,
+        }
+    }
+
+    pub fn wrapping_neg(self) -> Fix32<N> {
+        Fix32 {
+            v: self.v.wrapping_neg(),
+        }
+    }
+}
+
+// Elementary functions. These are evaluated with 40 fractional bits and
+// rounded once. For Q27 to Q30 the error is below 0.6 ulp (2^-N) over the
+// whole input range (for powi with |n| <= 100), checked against f64 in
+// tests/fixmath.rs. With fewer fractional bits, results far above 1 (tan
+// close to its poles, negative powers of small numbers) are less accurate,
+// and N has to be at least 9. Results outside the range of Fix32<N>
+// saturate, arguments outside the domain give the saturated limit (ln,
+// inv_sqrt, recip) or are clamped (asin, acos).
+impl<const N: usize> Fix32<N> {
+    pub fn recip(&self) -> Fix32<N> {
+        if self.v == 0 {
+            return Self::MAX;
+        }
+        let q = wide::div_round(1i128 << (2 * N), self.v as i128);
+        Self::saturate(q.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+
+    pub fn inv_sqrt(&self) -> Fix32<N> {
+        if self.v <= 0 {
+            return Self::MAX;
+        }
+        let r = wide::isqrt((1u128 << (3 * N)) / self.v as u128);
+        Fix32 {
+            v: r.min(i32::MAX as u128) as i32,
+        }
+    }
+
+    pub fn exp(&self) -> Fix32<N> {
+        let x = wide::from_fix(self.v, N);
+        // x = k ln2 + r with |r| <= ln2 / 2
+        let k = wide::div_round(x as i128, wide::LN2 as i128) as i64;
+        let r = x - wide::mul_q62(k, wide::LN2_Q62);
+        let mut p = wide::ONE;
+        for i in (1..=12).rev() {
+            p = wide::ONE + wide::mul(r, p) / i;
+        }
+        let shift = k - (wide::W - N as u32) as i64;
+        if shift >= 0 {
+            if shift > 31 || p > (i32::MAX as i64) >> shift {
+                return Self::MAX;
+            }
+            Fix32 {
+                v: (p << shift) as i32,
+            }
+        } else {
+            Self::saturate(wide::shr_round(p, (-shift) as u32))
+        }
+    }
+
+    pub fn ln(&self) -> Fix32<N> {
+        if self.v <= 0 {
+            return Self::MIN;
+        }
+        // self = m 2^e with m in [sqrt(1/2), sqrt(2))
+        let hb = 31 - self.v.leading_zeros() as i64;
+        let mut m = (self.v as i64) << (wide::W as i64 - hb);
+        let mut e = hb - N as i64;
+        if m > wide::SQRT2 {
+            m >>= 1;
+            e += 1;
+        }
+        // ln m = 2 atanh((m - 1) / (m + 1))
+        let s = wide::div(m - wide::ONE, m + wide::ONE);
+        let s2 = wide::mul(s, s);
+        let mut p = wide::ONE / 17;
+        for k in (0..8).rev() {
+            p = wide::ONE / (2 * k + 1) + wide::mul(s2, p);
+        }
+        let ln = 2 * wide::mul(s, p) + wide::mul_q62(e, wide::LN2_Q62);
+        Self::saturate(wide::to_fix(ln, N))
+    }
+
+    pub fn powi(&self, n: i32) -> Fix32<N> {
+        let negative = self.v < 0 && n % 2 != 0;
+        let overflow = if negative { Self::MIN } else { Self::MAX };
+
+        let mut base = wide::from_fix(self.v, N);
+        // anything larger does not fit Fix32<N>, products are checked before
+        // they are narrowed to i64
+        let limit = 1i128 << (wide::W + 31 - N as u32);
+        let mul = |a: i64, b: i64| {
+            let p = wide::mul_wide(a, b);
+            (p.abs() <= limit).then_some(p as i64)
+        };
+        // negative powers of |self| >= 1 are taken of the reciprocal so that
+        // intermediate values stay small
+        let invert = n < 0 && base.abs() < wide::ONE;
+        if n < 0 && !invert {
+            base = wide::div(wide::ONE, base);
+        }
+        let mut acc = wide::ONE;
+        let mut e = n.unsigned_abs();
+        while e > 0 {
+            // with |base| >= 1 the remaining factors only grow
+            if e & 1 == 1 {
+                let Some(p) = mul(acc, base) else {
+                    return overflow;
+                };
+                acc = p;
+            }
+            e >>= 1;
+            if e > 0 {
+                let Some(p) = mul(base, base) else {
+                    return overflow;
+                };
+                base = p;
+            }
+        }
+        if invert {
+            if acc == 0 {
+                return overflow;
+            }
+            acc = wide::div(wide::ONE, acc);
+        }
+        Self::saturate(wide::to_fix(acc, N))
+    }
+
+    pub fn tan(&self) -> Fix32<N> {
+        let (s, c) = wide::sin_cos(wide::from_fix(self.v, N));
+        if c == 0 {
+            return if s < 0 { Self::MIN } else { Self::MAX };
+        }
+        Self::saturate(wide::to_fix(wide::div(s, c), N))
+    }
+
+    pub fn atan(&self) -> Fix32<N> {
+        let x = wide::from_fix(self.v, N);
+        Self::saturate(wide::to_fix(wide::atan2(x, wide::ONE), N))
+    }
+
+    pub fn asin(&self) -> Fix32<N> {
+        let x = wide::from_fix(self.v, N).clamp(-wide::ONE, wide::ONE);
+        Self::saturate(wide::to_fix(wide::atan2(x, wide::cos_of_sin(x)), N))
+    }
+
+    pub fn acos(&self) -> Fix32<N> {
+        let x = wide::from_fix(self.v, N).clamp(-wide::ONE, wide::ONE);
+        Self::saturate(wide::to_fix(wide::atan2(wide::cos_of_sin(x), x), N))
+    }
+}
+
+impl<const N: usize> Fix32<N> {
+    const PI_6: Fix32<N> = Self::from_fixed(2414673152692374281i64, 62);
+    const SQRT3: Fix32<N> = Self::from_fixed(7987674492471257551i64, 62);
+    const TAN_PI_12: Fix32<N> = Self::from_fixed(1235697544383518257i64, 62);
+
+    pub fn sin_with(&self, precision: Precision) -> Fix32<N> {
+        match precision {
+            Precision::Low => self.sin(),
+            Precision::Medium => self.sin_cos_medium().0,
+            Precision::High => {
+                let (s, _) = wide::sin_cos(wide::from_fix(self.v, N));
+                Self::saturate(wide::to_fix(s, N))
+            }
+        }
+    }
+
+    pub fn cos_with(&self, precision: Precision) -> Fix32<N> {
+        match precision {
+            Precision::Low => self.cos(),
+            Precision::Medium => self.sin_cos_medium().1,
+            Precision::High => {
+                let (_, c) = wide::sin_cos(wide::from_fix(self.v, N));
+                Self::saturate(wide::to_fix(c, N))
+            }
+        }
+    }
+
+    // atan2(0, 0) is 0 for Medium and High, Low panics on it in debug builds
+    pub fn atan2_with(&self, x: Fix32<N>, precision: Precision) -> Fix32<N> {
+        match precision {
+            Precision::Low => self.atan2(x),
+            Precision::Medium => self.atan2_medium(x),
+            Precision::High => {
+                let a = wide::atan2(wide::from_fix(self.v, N), wide::from_fix(x.v, N));
+                Self::saturate(wide::to_fix(a, N))
+            }
+        }
+    }
+
+    fn sin_cos_medium(&self) -> (Fix32<N>, Fix32<N>) {
+        // self = k pi/2 + r with |r| <= pi/4
+        let k = wide::div_round(self.v as i128, Self::HALF_PI.v as i128) as i64;
+        let r = Self::saturate(self.v as i64 - k * Self::HALF_PI.v as i64);
+        let r2 = r * r;
+        let one = Self::from_i32(1);
+        let (mut s, mut c) = (one, one);
+        for i in (1..=5).rev() {
+            s = one - (r2 * s).div_int((2 * i) * (2 * i + 1));
+            c = one - (r2 * c).div_int((2 * i - 1) * (2 * i));
+        }
+        let s = r * s;
+        match k.rem_euclid(4) {
+            0 => (s, c),
+            1 => (c, -s),
+            2 => (-s, -c),
+            _ => (-c, s),
+        }
+    }
+
+    // integer divisors may be outside the range of Fix32<N>
+    fn div_int(self, d: i32) -> Fix32<N> {
+        Fix32 {
+            v: wide::div_round(self.v as i128, d as i128) as i32,
+        }
+    }
+
+    fn atan2_medium(&self, x: Fix32<N>) -> Fix32<N> {
+        if self.v == 0 && x.v == 0 {
+            return Self::ZERO;
+        }
+        let one = Self::from_i32(1);
+        let a = Fix32::from_raw(self.v.saturating_abs());
+        let b = Fix32::from_raw(x.v.saturating_abs());
+        let (t, flip) = if a <= b {
+            (a / b, false)
+        } else {
+            (b / a, true)
+        };
+        // as in wide::atan_unit
+        let (base, t) = if t > Self::TAN_PI_12 {
+            (Self::PI_6, (t * Self::SQRT3 - one) / (t + Self::SQRT3))
+        } else {
+            (Self::ZERO, t)
+        };
+        let t2 = t * t;
+        let mut p = one.div_int(13);
+        for k in (0..6).rev() {
+            p = one.div_int(2 * k + 1) - t2 * p;
+        }
+        let angle = base + t * p;
+        let angle = if flip { Self::HALF_PI - angle } else { angle };
+        let angle = if x.v < 0 { Self::PI - angle } else { angle };
+        if self.v < 0 {
+            -angle
+        } else {
+            angle
+        }
+    }
 }
 
+// Conversions between Q formats. Dropping fractional bits rounds half away
+// from zero like the operators, values outside the target range saturate
+// (checked_ variants return None instead). Fix64 has the inverse direction.
+impl<const N: usize> Fix32<N> {
+    pub fn convert<const M: usize>(self) -> Fix32<M> {
+        if N == M {
+            return Fix32::from_raw(self.v);
+        }
+        Fix64::<N>::from(self).to_fix32()
+    }
+
+    pub fn checked_convert<const M: usize>(self) -> Option<Fix32<M>> {
+        Fix64::<N>::from(self).checked_to_fix32()
+    }
+
+    // None unless the value is representable in Q`M` without rounding
+    pub fn exact_convert<const M: usize>(self) -> Option<Fix32<M>> {
+        let r = self.checked_convert::<M>()?;
+        (r.convert::<N>() == self).then_some(r)
+    }
+
+    pub fn to_fix64<const M: usize>(self) -> Fix64<M> {
+        Fix64::<N>::from(self).convert()
+    }
+
+    // product of two formats rounded once into a third, e.g. a Q16 gain
+    // applied to a Q27 sample
+    pub fn mul_mixed<const M: usize, const O: usize>(self, rhs: Fix32<M>) -> Fix32<O> {
+        Fix64::<N>::from(self).mul_mixed::<M, O>(rhs).to_fix32()
+    }
+
+    // full width product for accumulating in Fix64, exact if O >= N + M
+    pub fn mul_fix64<const M: usize, const O: usize>(self, rhs: Fix32<M>) -> Fix64<O> {
+        Fix64::<N>::from(self).mul_mixed(rhs)
+    }
+}
+
+// Add, Sub and Neg overflow like the integer operators (panicking in debug
+// builds), Mul and Div wrap. The checked_, saturating_ and wrapping_ methods
+// pick the behaviour explicitly.
+
 impl<const N: usize> Add for Fix32<N> {
     type Output = Fix32<N>;
 
@@ -193,10 +602,7 @@ impl<const N: usize> Mul for Fix32<N> {
     type Output = Fix32<N>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let val = (self.v as i64) * (rhs.v as i64) / (Self::MULT as i64 / 2);
-        Fix32 {
-            v: ((val / 2) + (val % 2)) as i32,
-        }
+        self.wrapping_mul(rhs)
     }
 }
 
@@ -204,10 +610,7 @@ impl<const N: usize> Div for Fix32<N> {
     type Output = Fix32<N>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        let val = (self.v as i64) * (Self::MULT as i64) * 2 / (rhs.v as i64);
-        Fix32 {
-            v: ((val / 2) + (val % 2)) as i32,
-        }
+        self.wrapping_div(rhs)
     }
 }
 
@@ -233,3 +636,169 @@ impl<const N: usize> Display for Fix32<N> {
         Ok(())
     }
 }
+
+// 40 fractional bit arithmetic in i64 with i128 products, used by the
+// elementary functions of Fix32 (Fix64 uses the Q62 constants and helpers)
+pub(crate) mod wide {
+    pub const W: u32 = 40;
+    pub const ONE: i64 = 1 << W;
+
+    // constants with 62 fractional bits
+    pub const LN2_Q62: i64 = 3196577161300663915;
+    pub const HALF_PI_Q62: i64 = 7244019458077122842;
+    pub const PI_6_Q62: i64 = 2414673152692374281;
+    pub const SQRT3_Q62: i64 = 7987674492471257551;
+    pub const TAN_PI_12_Q62: i64 = 1235697544383518257;
+    const SQRT2_Q62: i64 = 6521908912666391106;
+
+    const fn from_q62(v: i64) -> i64 {
+        (v + (1 << 21)) >> 22
+    }
+
+    pub const LN2: i64 = from_q62(LN2_Q62);
+    pub const HALF_PI: i64 = from_q62(HALF_PI_Q62);
+    pub const SQRT2: i64 = from_q62(SQRT2_Q62);
+    const PI_6: i64 = from_q62(PI_6_Q62);
+    const SQRT3: i64 = from_q62(SQRT3_Q62);
+    const TAN_PI_12: i64 = from_q62(TAN_PI_12_Q62);
+
+    // Fix32<N> needs 9 <= N <= 31: its 31 integer bits above W fractional
+    // bits and the powi limit have to fit
+    pub fn from_fix(v: i32, n: usize) -> i64 {
+        debug_assert!((9..=31).contains(&n), "Q{} is not supported", n);
+        (v as i64) << (W - n as u32)
+    }
+
+    pub fn to_fix(v: i64, n: usize) -> i64 {
+        debug_assert!((9..=31).contains(&n), "Q{} is not supported", n);
+        shr_round(v, W - n as u32)
+    }
+
+    // rounds half away from zero
+    pub fn shr_round(v: i64, s: u32) -> i64 {
+        if s == 0 {
+            return v;
+        }
+        if s > 62 {
+            return 0;
+        }
+        let (v, half) = (v as i128, 1i128 << (s - 1));
+        (if v >= 0 {
+            (v + half) >> s
+        } else {
+            -((-v + half) >> s)
+        }) as i64
+    }
+
+    pub fn div_round(a: i128, b: i128) -> i128 {
+        let (q, r) = (a / b, a % b);
+        if 2 * r.abs() >= b.abs() {
+            q + if (a < 0) == (b < 0) { 1 } else { -1 }
+        } else {
+            q
+        }
+    }
+
+    pub fn mul(a: i64, b: i64) -> i64 {
+        mul_wide(a, b) as i64
+    }
+
+    // product before narrowing
+    pub fn mul_wide(a: i64, b: i64) -> i128 {
+        (a as i128 * b as i128 + (1 << (W - 1))) >> W
+    }
+
+    // integer times a Q62 constant
+    pub fn mul_q62(k: i64, c: i64) -> i64 {
+        ((k as i128 * c as i128 + (1 << 21)) >> 22) as i64
+    }
+
+    // saturates to the i64 range
+    pub fn div(a: i64, b: i64) -> i64 {
+        let q = div_round((a as i128) << W, b as i128);
+        q.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    // rounded to nearest
+    pub fn isqrt(n: u128) -> u128 {
+        if n == 0 {
+            return 0;
+        }
+        let mut num = n;
+        let mut res = 0u128;
+        let mut bit = 1u128 << ((127 - n.leading_zeros()) & !1);
+        while bit != 0 {
+            if num >= res + bit {
+                num -= res + bit;
+                res = (res >> 1) + bit;
+            } else {
+                res >>= 1;
+            }
+            bit >>= 2;
+        }
+        if num > res {
+            res += 1;
+        }
+        res
+    }
+
+    // sqrt(1 - x^2) for |x| <= 1
+    pub fn cos_of_sin(x: i64) -> i64 {
+        let one = (ONE as i128) * (ONE as i128);
+        isqrt((one - x as i128 * x as i128) as u128) as i64
+    }
+
+    pub fn sin_cos(x: i64) -> (i64, i64) {
+        // x = k pi/2 + r with |r| <= pi/4
+        let k = div_round(x as i128, HALF_PI as i128) as i64;
+        let r = x - mul_q62(k, HALF_PI_Q62);
+        let r2 = mul(r, r);
+        let (mut s, mut c) = (ONE, ONE);
+        for i in (1..=7).rev() {
+            s = ONE - mul(r2, s) / ((2 * i) * (2 * i + 1));
+            c = ONE - mul(r2, c) / ((2 * i - 1) * (2 * i));
+        }
+        let s = mul(r, s);
+        match k.rem_euclid(4) {
+            0 => (s, c),
+            1 => (c, -s),
+            2 => (-s, -c),
+            _ => (-c, s),
+        }
+    }
+
+    // atan for 0 <= t <= 1
+    fn atan_unit(t: i64) -> i64 {
+        // atan t = pi/6 + atan((t sqrt3 - 1) / (t + sqrt3)) moves t below
+        // tan(pi/12) where the series converges quickly
+        let (base, t) = if t > TAN_PI_12 {
+            (PI_6, div(mul(t, SQRT3) - ONE, t + SQRT3))
+        } else {
+            (0, t)
+        };
+        let t2 = mul(t, t);
+        let mut p = ONE / 21;
+        for k in (0..10).rev() {
+            p = ONE / (2 * k + 1) - mul(t2, p);
+        }
+        base + mul(t, p)
+    }
+
+    pub fn atan2(y: i64, x: i64) -> i64 {
+        if x == 0 && y == 0 {
+            return 0;
+        }
+        let (a, b) = (y.abs(), x.abs());
+        let angle = if a <= b {
+            atan_unit(div(a, b))
+        } else {
+            HALF_PI - atan_unit(div(b, a))
+        };
+        let angle = if x < 0 { 2 * HALF_PI - angle } else { angle };
+        if y < 0 {
+            -angle
+        } else {
+            angle
+        }
+    }
+}
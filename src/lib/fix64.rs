@@ -0,0 +1,394 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::fix32::{wide, Fix32};
+
+// i64 backed fixed point with N fractional bits, for accumulators and
+// intermediate results that would lose precision or overflow in Fix32.
+// Products and quotients use i128 and round half away from zero like Fix32.
+// N up to 60 is supported (PI needs 2 integer bits).
+#[derive(Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
+pub struct Fix64<const N: usize> {
+    v: i64,
+}
+
+impl<const N: usize> Fix64<N> {
+    pub const MULT: i64 = 1i64 << N;
+
+    pub const E: Fix64<N> = Self::from_fixed(6267931151224907085i64, 61);
+    pub const PI: Fix64<N> = Self::from_fixed(7244019458077122842i64, 61);
+    pub const HALF_PI: Fix64<N> = Self::from_fixed(7244019458077122842i64, 62);
+    pub const TWO_PI: Fix64<N> = Self::from_fixed(7244019458077122842i64, 60);
+
+    pub const ZERO: Fix64<N> = Fix64 { v: 0 };
+    pub const MAX: Fix64<N> = Fix64 { v: i64::MAX };
+    pub const MIN: Fix64<N> = Fix64 { v: i64::MIN };
+
+    const fn from_fixed(v: i64, s: usize) -> Fix64<N> {
+        if s > N {
+            Fix64 {
+                v: v / (1 << (s - N)) + (v / (1 << (s - N - 1)) % 2),
+            }
+        } else {
+            Fix64 {
+                v: v * (1 << (N - s)),
+            }
+        }
+    }
+
+    pub const fn from_raw(x: i64) -> Fix64<N> {
+        Fix64 { v: x }
+    }
+
+    pub fn from_i32(x: i32) -> Fix64<N> {
+        Fix64 {
+            v: x as i64 * Self::MULT,
+        }
+    }
+
+    pub fn to_raw(&self) -> i64 {
+        self.v
+    }
+
+    pub fn from_float(x: f64) -> Fix64<N> {
+        Fix64 {
+            v: (x * Self::MULT as f64).round() as i64,
+        }
+    }
+
+    pub fn to_float(&self) -> f64 {
+        self.v as f64 / Self::MULT as f64
+    }
+
+    // Evaluated with 62 fractional bits and rounded once, below 0.6 ulp for
+    // N <= 56 (Q27 and Q48 are checked in tests/fix64.rs).
+
+    pub fn sin(&self) -> Fix64<N> {
+        Self::from_q62(q62::sin_cos(self.v, N).0)
+    }
+
+    pub fn cos(&self) -> Fix64<N> {
+        Self::from_q62(q62::sin_cos(self.v, N).1)
+    }
+
+    pub fn atan2(&self, x: Fix64<N>) -> Fix64<N> {
+        Self::from_q62(q62::atan2(self.v, x.v))
+    }
+
+    // rounded to nearest
+    pub fn sqrt(&self) -> Fix64<N> {
+        debug_assert!(self.v >= 0);
+        if self.v <= 0 {
+            return Self::ZERO;
+        }
+        Fix64 {
+            v: wide::isqrt((self.v as u128) << N) as i64,
+        }
+    }
+
+    pub fn fmod(&self, m: Fix64<N>) -> Fix64<N> {
+        Fix64 { v: self.v % m.v }
+    }
+
+    fn from_q62(v: i128) -> Fix64<N> {
+        Self::saturate(rescale(v, 62, N))
+    }
+
+    fn mul_wide(self, rhs: Fix64<N>) -> i128 {
+        rescale(self.v as i128 * rhs.v as i128, 2 * N, N)
+    }
+
+    fn div_wide(self, rhs: Fix64<N>) -> Option<i128> {
+        if rhs.v == 0 {
+            return None;
+        }
+        Some(wide::div_round((self.v as i128) << N, rhs.v as i128))
+    }
+
+    fn saturate(v: i128) -> Fix64<N> {
+        Fix64 {
+            v: v.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+        }
+    }
+
+    pub fn checked_add(self, rhs: Fix64<N>) -> Option<Fix64<N>> {
+        self.v.checked_add(rhs.v).map(Self::from_raw)
+    }
+
+    pub fn checked_sub(self, rhs: Fix64<N>) -> Option<Fix64<N>> {
+        self.v.checked_sub(rhs.v).map(Self::from_raw)
+    }
+
+    pub fn checked_mul(self, rhs: Fix64<N>) -> Option<Fix64<N>> {
+        i64::try_from(self.mul_wide(rhs)).ok().map(Self::from_raw)
+    }
+
+    // None on division by zero as well
+    pub fn checked_div(self, rhs: Fix64<N>) -> Option<Fix64<N>> {
+        let v = self.div_wide(rhs)?;
+        i64::try_from(v).ok().map(Self::from_raw)
+    }
+
+    pub fn checked_neg(self) -> Option<Fix64<N>> {
+        self.v.checked_neg().map(Self::from_raw)
+    }
+
+    pub fn saturating_add(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.v.saturating_add(rhs.v),
+        }
+    }
+
+    pub fn saturating_sub(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.v.saturating_sub(rhs.v),
+        }
+    }
+
+    pub fn saturating_mul(self, rhs: Fix64<N>) -> Fix64<N> {
+        Self::saturate(self.mul_wide(rhs))
+    }
+
+    // division by zero gives MAX or MIN by the sign of self, 0 / 0 gives 0
+    pub fn saturating_div(self, rhs: Fix64<N>) -> Fix64<N> {
+        match self.div_wide(rhs) {
+            Some(v) => Self::saturate(v),
+            None => match self.v.cmp(&0) {
+                std::cmp::Ordering::Less => Self::MIN,
+                std::cmp::Ordering::Equal => Self::ZERO,
+                std::cmp::Ordering::Greater => Self::MAX,
+            },
+        }
+    }
+
+    pub fn saturating_neg(self) -> Fix64<N> {
+        Fix64 {
+            v: self.v.saturating_neg(),
+        }
+    }
+
+    pub fn wrapping_add(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.v.wrapping_add(rhs.v),
+        }
+    }
+
+    pub fn wrapping_sub(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.v.wrapping_sub(rhs.v),
+        }
+    }
+
+    pub fn wrapping_mul(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.mul_wide(rhs) as i64,
+        }
+    }
+
+    // panics on division by zero like i64::wrapping_div
+    pub fn wrapping_div(self, rhs: Fix64<N>) -> Fix64<N> {
+        Fix64 {
+            v: self.div_wide(rhs).expect("division by zero") as i64,
+        }
+    }
+
+    pub fn wrapping_neg(self) -> Fix64<N> {
+        Fix64 {
+            v: self.v.wrapping_neg(),
+        }
+    }
+}
+
+// Conversions between Q formats and to Fix32. Dropping fractional bits
+// rounds half away from zero, values outside the target range saturate
+// (checked_ variants return None instead).
+impl<const N: usize> Fix64<N> {
+    pub fn convert<const M: usize>(self) -> Fix64<M> {
+        Fix64::saturate(rescale(self.v as i128, N, M))
+    }
+
+    pub fn checked_convert<const M: usize>(self) -> Option<Fix64<M>> {
+        i64::try_from(rescale(self.v as i128, N, M))
+            .ok()
+            .map(Fix64::from_raw)
+    }
+
+    pub fn to_fix32<const M: usize>(self) -> Fix32<M> {
+        let v = rescale(self.v as i128, N, M);
+        Fix32::from_raw(v.clamp(i32::MIN as i128, i32::MAX as i128) as i32)
+    }
+
+    pub fn checked_to_fix32<const M: usize>(self) -> Option<Fix32<M>> {
+        i32::try_from(rescale(self.v as i128, N, M))
+            .ok()
+            .map(Fix32::from_raw)
+    }
+
+    // accumulator times coefficient in any format, without narrowing the
+    // product first
+    pub fn mul_mixed<const M: usize, const O: usize>(self, rhs: Fix32<M>) -> Fix64<O> {
+        Fix64::saturate(rescale(self.v as i128 * rhs.to_raw() as i128, N + M, O))
+    }
+}
+
+impl<const N: usize> From<Fix32<N>> for Fix64<N> {
+    fn from(x: Fix32<N>) -> Self {
+        Fix64 {
+            v: x.to_raw() as i64,
+        }
+    }
+}
+
+// changes the number of fractional bits of v, rounding half away from zero
+// when bits are dropped; saturates to the i128 range when shifting left
+pub(crate) fn rescale(v: i128, from: usize, to: usize) -> i128 {
+    if to >= from {
+        let s = (to - from) as u32;
+        if v != 0 && v.unsigned_abs().leading_zeros() <= s {
+            return if v < 0 { i128::MIN } else { i128::MAX };
+        }
+        v << s
+    } else {
+        let s = (from - to) as u32;
+        let half = 1i128 << (s - 1);
+        if v >= 0 {
+            (v + half) >> s
+        } else {
+            -((half - v) >> s)
+        }
+    }
+}
+
+// The operators overflow the same way as the Fix32 ones.
+
+impl<const N: usize> Add for Fix64<N> {
+    type Output = Fix64<N>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Fix64 { v: self.v + rhs.v }
+    }
+}
+
+impl<const N: usize> Sub for Fix64<N> {
+    type Output = Fix64<N>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Fix64 { v: self.v - rhs.v }
+    }
+}
+
+impl<const N: usize> Mul for Fix64<N> {
+    type Output = Fix64<N>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.wrapping_mul(rhs)
+    }
+}
+
+impl<const N: usize> Div for Fix64<N> {
+    type Output = Fix64<N>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.wrapping_div(rhs)
+    }
+}
+
+impl<const N: usize> Neg for Fix64<N> {
+    type Output = Fix64<N>;
+
+    fn neg(self) -> Self::Output {
+        Fix64 { v: -self.v }
+    }
+}
+
+impl<const N: usize> Debug for Fix64<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fix64")
+            .field("v", &self.to_float())
+            .finish()
+    }
+}
+
+impl<const N: usize> Display for Fix64<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(format!("{}", self.to_float()).as_str())?;
+        Ok(())
+    }
+}
+
+// 62 fractional bit arithmetic in i128 for the trigonometry of Fix64,
+// operands stay below 4 so products do not overflow
+mod q62 {
+    use super::{rescale, wide};
+
+    const ONE: i128 = 1 << 62;
+    const HALF_PI: i128 = wide::HALF_PI_Q62 as i128;
+    // the next 62 bits of pi/2, for the range reduction of large arguments
+    const HALF_PI_LO: i128 = 1772391103515558810;
+    const PI_6: i128 = wide::PI_6_Q62 as i128;
+    const SQRT3: i128 = wide::SQRT3_Q62 as i128;
+    const TAN_PI_12: i128 = wide::TAN_PI_12_Q62 as i128;
+
+    fn mul(a: i128, b: i128) -> i128 {
+        rescale(a * b, 124, 62)
+    }
+
+    fn div(a: i128, b: i128) -> i128 {
+        wide::div_round(a << 62, b)
+    }
+
+    // sin and cos of x with n fractional bits
+    pub fn sin_cos(x: i64, n: usize) -> (i128, i128) {
+        // x = k pi/2 + r with |r| <= pi/4
+        let x = (x as i128) << (62 - n);
+        let k = wide::div_round(x, HALF_PI);
+        let r = x - k * HALF_PI - rescale(k * HALF_PI_LO, 124, 62);
+        let r2 = mul(r, r);
+        let (mut s, mut c) = (ONE, ONE);
+        for i in (1..=12).rev() {
+            s = ONE - mul(r2, s) / ((2 * i) * (2 * i + 1));
+            c = ONE - mul(r2, c) / ((2 * i - 1) * (2 * i));
+        }
+        let s = mul(r, s);
+        match k.rem_euclid(4) {
+            0 => (s, c),
+            1 => (c, -s),
+            2 => (-s, -c),
+            _ => (-c, s),
+        }
+    }
+
+    // atan for 0 <= t <= 1, same reduction as the 40-bit version
+    fn atan_unit(t: i128) -> i128 {
+        let (base, t) = if t > TAN_PI_12 {
+            (PI_6, div(mul(t, SQRT3) - ONE, t + SQRT3))
+        } else {
+            (0, t)
+        };
+        let t2 = mul(t, t);
+        let mut p = 0;
+        for k in (0..17).rev() {
+            p = wide::div_round(ONE, 2 * k + 1) - mul(t2, p);
+        }
+        base + mul(t, p)
+    }
+
+    // the scale of y and x cancels
+    pub fn atan2(y: i64, x: i64) -> i128 {
+        if x == 0 && y == 0 {
+            return 0;
+        }
+        let (a, b) = ((y as i128).abs(), (x as i128).abs());
+        let angle = if a <= b {
+            atan_unit(div(a, b))
+        } else {
+            HALF_PI - atan_unit(div(b, a))
+        };
+        let angle = if x < 0 { 2 * HALF_PI - angle } else { angle };
+        if y < 0 {
+            -angle
+        } else {
+            angle
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::Display;
+
+use crate::{
+    esplog::{self, Writer},
+    quat::{Fix, Quat, RVec},
+};
+
+// Float entry points around the fixed-point codec. Inputs are validated
+// before conversion to Fix32<27>, whose range is +-16: quaternions must be
+// finite and close to unit length, per-sample rotations must stay below pi.
+
+pub const MAX_NORM_ERROR: f32 = 0.01;
+pub const MAX_ROTATION_PER_SAMPLE: f32 = std::f32::consts::PI;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Error {
+    NotFinite { index: usize },
+    NotNormalized { index: usize, norm: f32 },
+    RotationTooLarge { index: usize, angle: f32 },
+    Log(esplog::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFinite { index } => write!(f, "sample {} is not finite", index),
+            Error::NotNormalized { index, norm } => {
+                write!(f, "quaternion {} has norm {}, expected 1", index, norm)
+            }
+            Error::RotationTooLarge { index, angle } => {
+                write!(f, "sample {} rotates by {} rad, more than pi", index, angle)
+            }
+            Error::Log(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<esplog::Error> for Error {
+    fn from(e: esplog::Error) -> Self {
+        Error::Log(e)
+    }
+}
+
+// `index` only goes into the error
+pub fn quat_from_f32(q: [f32; 4], index: usize) -> Result<Quat, Error> {
+    if q.iter().any(|x| !x.is_finite()) {
+        return Err(Error::NotFinite { index });
+    }
+    let norm = q.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if (norm - 1.0).abs() > MAX_NORM_ERROR {
+        return Err(Error::NotNormalized { index, norm });
+    }
+    let [w, x, y, z] = q.map(|x| Fix::from_float(x / norm));
+    Ok(Quat::new(w, x, y, z))
+}
+
+pub fn quat_to_f32(q: &Quat) -> [f32; 4] {
+    [q.w, q.x, q.y, q.z].map(|x| x.to_float())
+}
+
+// rotation vector of one gyro sample, `rate` in rad/s and `dt` in s
+pub fn rvec_from_rate(rate: [f32; 3], dt: f32, index: usize) -> Result<RVec, Error> {
+    let v = rate.map(|x| x * dt);
+    if v.iter().any(|x| !x.is_finite()) {
+        return Err(Error::NotFinite { index });
+    }
+    let angle = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if angle > MAX_ROTATION_PER_SAMPLE {
+        return Err(Error::RotationTooLarge { index, angle });
+    }
+    let [x, y, z] = v.map(Fix::from_float);
+    Ok(RVec::new(x, y, z))
+}
+
+// Streaming encoder taking float samples, gyro blocks are written whenever
+// `block_size` samples have been collected. Gyro rates are integrated into
+// orientation and a time block closes every gyro block.
+pub struct Encoder {
+    writer: Writer,
+    block_size: usize,
+    pending: Vec<Quat>,
+    q: Quat,
+    samples: usize,
+    dt_us: f64,
+}
+
+impl Encoder {
+    pub fn new(qp: u8, block_size: u16) -> Result<Encoder, Error> {
+        Ok(Encoder {
+            writer: Writer::new(qp)?,
+            block_size: block_size.max(1) as usize,
+            pending: Vec::with_capacity(block_size as usize),
+            q: Quat::default(),
+            samples: 0,
+            dt_us: 0.0,
+        })
+    }
+
+    pub fn push_quat(&mut self, q: [f32; 4]) -> Result<(), Error> {
+        let q = quat_from_f32(q, self.samples)?;
+        self.push(q)
+    }
+
+    pub fn push_gyro(&mut self, rate: [f32; 3], dt: f32) -> Result<(), Error> {
+        let v = rvec_from_rate(rate, dt, self.samples)?;
+        self.q = (self.q * Quat::from_rvec(&v)).normalize_safe();
+        self.dt_us += dt as f64 * 1e6;
+        self.push(self.q)
+    }
+
+    fn push(&mut self, q: Quat) -> Result<(), Error> {
+        self.pending.push(q);
+        self.samples += 1;
+        if self.pending.len() == self.block_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.gyro_data(&self.pending)?;
+        self.pending.clear();
+        if self.dt_us > 0.0 {
+            let dt = self.dt_us.round();
+            self.writer.time(dt as u32);
+            self.dt_us -= dt;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<u8>, Error> {
+        if !self.pending.is_empty() {
+            self.flush()?;
+        }
+        Ok(self.writer.finish())
+    }
+}
+
+pub fn encode_quats(quats: &[[f32; 4]], qp: u8, block_size: u16) -> Result<Vec<u8>, Error> {
+    let mut enc = Encoder::new(qp, block_size)?;
+    for q in quats {
+        enc.push_quat(*q)?;
+    }
+    enc.finish()
+}
+
+pub fn encode_gyro(rates: &[[f32; 3]], dt: f32, qp: u8, block_size: u16) -> Result<Vec<u8>, Error> {
+    let mut enc = Encoder::new(qp, block_size)?;
+    for r in rates {
+        enc.push_gyro(*r, dt)?;
+    }
+    enc.finish()
+}
+
+pub fn decode_quats(buf: &[u8]) -> Result<Vec<[f32; 4]>, Error> {
+    Ok(esplog::decode(buf)?.quats.iter().map(quat_to_f32).collect())
+}
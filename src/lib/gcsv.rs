@@ -0,0 +1,347 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::{
+    esplog::{self, Log},
+    metadata,
+    orientation::ImuOrientation,
+    quat::{Fix, Quat, RVec},
+};
+
+// Gyroflow IMU log (.gcsv): a "GYROFLOW IMU LOG" line, `key,value` header
+// lines, a column header starting with `t` and integer (or float) rows which
+// are scaled to physical units by tscale, gscale, ascale and mscale.
+
+pub const MAGIC: &str = "GYROFLOW IMU LOG";
+
+// used when the log has no gyro_scale metadata, 2000 deg/s over 16 bits
+pub const DEFAULT_GSCALE: f64 = 0.001_065_264_4;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse { line: usize, msg: String },
+    Log(esplog::Error),
+    NoTimestamps,
+    InvalidAccelRange { range: u8 },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { line, msg } => write!(f, "line {}: {}", line, msg),
+            Error::Log(e) => write!(f, "{}", e),
+            Error::NoTimestamps => write!(f, "log has no time blocks and no sample rate"),
+            Error::InvalidAccelRange { range } => {
+                write!(f, "accel range 2^{} g is out of range", range)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<esplog::Error> for Error {
+    fn from(e: esplog::Error) -> Self {
+        Error::Log(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub version: String,
+    pub id: String,
+    pub orientation: Option<ImuOrientation>,
+    pub tscale: f64,         // seconds per t unit
+    pub gscale: f64,         // rad/s per gyro unit
+    pub ascale: Option<f64>, // g per accel unit
+    pub mscale: Option<f64>, // magnetometer scale
+    pub extra: Vec<(String, String)>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub t: f64,         // s
+    pub gyro: [f64; 3], // rad/s
+    pub accel: Option<[f64; 3]>,
+    pub mag: Option<[f64; 3]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gcsv {
+    pub header: Header,
+    pub samples: Vec<Sample>,
+}
+
+// column indices of a vector quantity
+type Columns = Option<[usize; 3]>;
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Gcsv, Error> {
+    parse(BufReader::new(File::open(path)?))
+}
+
+pub fn parse<R: BufRead>(reader: R) -> Result<Gcsv, Error> {
+    let mut lines = reader.lines().enumerate().map(|(i, l)| (i + 1, l));
+    let err = |line: usize, msg: String| Error::Parse { line, msg };
+
+    let first = lines.next().map(|(_, l)| l).transpose()?;
+    if first.as_deref().map(str::trim) != Some(MAGIC) {
+        return Err(err(1, format!("expected \"{}\"", MAGIC)));
+    }
+
+    let mut header = Header {
+        version: String::new(),
+        id: String::new(),
+        orientation: None,
+        tscale: 0.0,
+        gscale: 0.0,
+        ascale: None,
+        mscale: None,
+        extra: vec![],
+    };
+    let mut tscale = None;
+    let mut gscale = None;
+    let mut last = 1;
+    let (columns, header_line) = loop {
+        let (n, line) = lines
+            .next()
+            .ok_or_else(|| err(last + 1, "missing column header".to_owned()))?;
+        last = n;
+        let line = line?;
+        let (key, value) = line.split_once(',').unwrap_or((line.as_str(), ""));
+        let (key, value) = (key.trim(), value.trim());
+        let scale = || {
+            value
+                .parse::<f64>()
+                .map_err(|_| err(n, format!("invalid {} \"{}\"", key, value)))
+        };
+        match key {
+            "t" => {
+                break (
+                    line.split(',')
+                        .map(|x| x.trim().to_owned())
+                        .collect::<Vec<_>>(),
+                    n,
+                )
+            }
+            "version" => header.version = value.to_owned(),
+            "id" => header.id = value.to_owned(),
+            "orientation" => {
+                header.orientation = Some(
+                    ImuOrientation::parse(value)
+                        .ok_or_else(|| err(n, format!("invalid orientation \"{}\"", value)))?,
+                )
+            }
+            "tscale" => tscale = Some(scale()?),
+            "gscale" => gscale = Some(scale()?),
+            "ascale" => header.ascale = Some(scale()?),
+            "mscale" => header.mscale = Some(scale()?),
+            "" => {}
+            _ => header.extra.push((key.to_owned(), value.to_owned())),
+        }
+    };
+    header.tscale = tscale.ok_or_else(|| err(header_line, "missing tscale".to_owned()))?;
+    header.gscale = gscale.ok_or_else(|| err(header_line, "missing gscale".to_owned()))?;
+
+    let col = |names: [&str; 3]| -> Columns {
+        let idx = names.map(|n| columns.iter().position(|c| c == n));
+        Some([idx[0]?, idx[1]?, idx[2]?])
+    };
+    let gyro_cols = col(["gx", "gy", "gz"])
+        .ok_or_else(|| err(header_line, "missing gyro columns".to_owned()))?;
+    let accel_cols = col(["ax", "ay", "az"]);
+    let mag_cols = col(["mx", "my", "mz"]);
+    if accel_cols.is_some() && header.ascale.is_none() {
+        return Err(err(header_line, "missing ascale".to_owned()));
+    }
+    if mag_cols.is_some() && header.mscale.is_none() {
+        return Err(err(header_line, "missing mscale".to_owned()));
+    }
+
+    let mut samples = vec![];
+    for (n, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = line
+            .split(',')
+            .map(|x| x.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err(n, format!("invalid number in \"{}\"", line)))?;
+        if fields.len() < columns.len() {
+            return Err(err(
+                n,
+                format!("expected {} fields, got {}", columns.len(), fields.len()),
+            ));
+        }
+        let vec3 = |cols: [usize; 3], scale: f64| cols.map(|i| fields[i] * scale);
+        samples.push(Sample {
+            t: fields[0] * header.tscale,
+            gyro: vec3(gyro_cols, header.gscale),
+            accel: accel_cols.map(|c| vec3(c, header.ascale.unwrap())),
+            mag: mag_cols.map(|c| vec3(c, header.mscale.unwrap())),
+        });
+    }
+
+    Ok(Gcsv { header, samples })
+}
+
+impl Gcsv {
+    // integrated orientation, one quaternion per sample; the first sample
+    // uses the interval to the second one
+    pub fn to_quats(&self) -> Vec<Quat> {
+        let mut q = Quat::default();
+        let s = &self.samples;
+        (0..s.len())
+            .map(|i| {
+                let dt = match i {
+                    0 if s.len() > 1 => s[1].t - s[0].t,
+                    0 => self.header.tscale,
+                    _ => s[i].t - s[i - 1].t,
+                };
+                let [x, y, z] = s[i].gyro.map(|g| Fix::from_float((g * dt) as f32));
+                q = q * Quat::from_rvec(&RVec::new(x, y, z));
+                q
+            })
+            .collect()
+    }
+
+    // Angular rate is recovered by differentiating the decoded orientation,
+    // accel is interpolated onto the gyro timestamps. Samples stay in the IMU
+    // frame, the orientation block goes into the header.
+    pub fn from_log(log: &Log) -> Result<Gcsv, Error> {
+        let timeline = log.timeline().ok_or(Error::NoTimestamps)?;
+        let t: Vec<f64> = timeline.quats.us.iter().map(|&x| x as f64 * 1e-6).collect();
+
+        let range_scale = |p: u8| {
+            1u32.checked_shl(p as u32)
+                .map(|r| r as f64 / 32768.0)
+                .ok_or(Error::InvalidAccelRange { range: p })
+        };
+        let accel_scale = match log.metadata.accel_scale() {
+            Some(s) => Some(s),
+            None => log.accel_range.map(range_scale).transpose()?,
+        };
+        let accel_t = &timeline.accels.us;
+        let accel_at = |t_us: f64, scale: f64| -> [f64; 3] {
+            let i = accel_t.partition_point(|&x| (x as f64) < t_us);
+            let a = |k: usize| log.accels[k].map(|x| x as f64 * scale);
+            if i == 0 || i == accel_t.len() {
+                return a(i.min(accel_t.len() - 1));
+            }
+            let (t0, t1) = (accel_t[i - 1] as f64, accel_t[i] as f64);
+            let k = (t_us - t0) / (t1 - t0);
+            let (a0, a1) = (a(i - 1), a(i));
+            [0, 1, 2].map(|j| a0[j] + (a1[j] - a0[j]) * k)
+        };
+        let has_accel = !log.accels.is_empty();
+
+        let mut prev = Quat::default();
+        let samples = (0..log.quats.len())
+            .map(|i| {
+                let dt = match i {
+                    0 if t.len() > 1 => t[1] - t[0],
+                    0 => 1.0 / timeline.quats.sample_rate,
+                    _ => t[i] - t[i - 1],
+                };
+                let q = log.quats[i];
+                // to_rvec needs w >= 0, see quat::atan2
+                let d = prev.conj() * q;
+                let v = if d.w < Fix::ZERO { -d } else { d }.to_rvec();
+                prev = q;
+                Sample {
+                    t: t[i],
+                    gyro: [v.x, v.y, v.z].map(|x| x.to_float() as f64 / dt),
+                    accel: accel_scale
+                        .filter(|_| has_accel)
+                        .map(|s| accel_at(t[i] * 1e6, s)),
+                    mag: None,
+                }
+            })
+            .collect();
+
+        let meta = &log.metadata;
+        let extra = [
+            ("fwversion", metadata::FIRMWARE_VERSION),
+            ("lensprofile", metadata::LENS_PROFILE),
+            ("vendor", metadata::DEVICE_MODEL),
+        ]
+        .iter()
+        .filter_map(|(k, m)| Some((k.to_string(), meta.get(m)?.as_str()?.to_owned())))
+        .collect();
+
+        Ok(Gcsv {
+            header: Header {
+                version: "1.1".to_owned(),
+                id: "esplog".to_owned(),
+                orientation: log.orientation()?,
+                tscale: 1e-6,
+                gscale: meta.gyro_scale().unwrap_or(DEFAULT_GSCALE),
+                ascale: accel_scale.filter(|_| has_accel),
+                mscale: None,
+                extra,
+            },
+            samples,
+        })
+    }
+
+    // rows are written as integers in units of the header scales
+    pub fn write<W: Write>(&self, w: W) -> Result<(), Error> {
+        let mut w = BufWriter::new(w);
+        let h = &self.header;
+        writeln!(w, "{}", MAGIC)?;
+        writeln!(w, "version,{}", h.version)?;
+        writeln!(w, "id,{}", h.id)?;
+        if let Some(o) = h.orientation {
+            writeln!(w, "orientation,{}", o)?;
+        }
+        for (k, v) in &h.extra {
+            writeln!(w, "{},{}", k, v)?;
+        }
+        writeln!(w, "tscale,{}", h.tscale)?;
+        writeln!(w, "gscale,{}", h.gscale)?;
+        let has_accel = h.ascale.is_some() && self.samples.iter().all(|s| s.accel.is_some());
+        let has_mag = h.mscale.is_some() && self.samples.iter().all(|s| s.mag.is_some());
+        let mut columns = "t,gx,gy,gz".to_owned();
+        if let (true, Some(a)) = (has_accel, h.ascale) {
+            writeln!(w, "ascale,{}", a)?;
+            columns += ",ax,ay,az";
+        }
+        if let (true, Some(m)) = (has_mag, h.mscale) {
+            writeln!(w, "mscale,{}", m)?;
+            columns += ",mx,my,mz";
+        }
+        writeln!(w, "{}", columns)?;
+
+        let raw = |x: f64, scale: f64| (x / scale).round() as i64;
+        for s in &self.samples {
+            let g = s.gyro.map(|x| raw(x, h.gscale));
+            write!(w, "{},{},{},{}", raw(s.t, h.tscale), g[0], g[1], g[2])?;
+            if let (true, Some(a), Some(scale)) = (has_accel, s.accel, h.ascale) {
+                let a = a.map(|x| raw(x, scale));
+                write!(w, ",{},{},{}", a[0], a[1], a[2])?;
+            }
+            if let (true, Some(m), Some(scale)) = (has_mag, s.mag, h.mscale) {
+                let m = m.map(|x| raw(x, scale));
+                write!(w, ",{},{},{}", m[0], m[1], m[2])?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write(File::create(path)?)
+    }
+}
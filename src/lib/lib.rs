@@ -1,4 +1,20 @@
+pub mod clock;
+pub mod compress;
+pub mod csv;
+pub mod esplog;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod fix32;
-pub mod quat;
+pub mod fix64;
+pub mod float;
+pub mod gcsv;
+pub mod metadata;
+pub mod metrics;
+pub mod orientation;
 pub mod quant;
-pub mod compress;
\ No newline at end of file
+pub mod quat;
+pub mod rawquat;
+pub mod scalar;
+pub mod sweep;
+pub mod timeline;
+pub mod version;
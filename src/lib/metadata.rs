@@ -0,0 +1,166 @@
+// Typed key-value pairs describing the device and the recording, stored in
+// metadata blocks (0x09) of the EspLog container.
+
+// well-known keys
+pub const DEVICE_MODEL: &str = "device_model";
+pub const FIRMWARE_VERSION: &str = "firmware_version";
+pub const IMU_CHIP: &str = "imu_chip";
+pub const LENS_PROFILE: &str = "lens_profile";
+pub const SAMPLE_RATE: &str = "sample_rate"; // Hz
+pub const TIME_SCALE: &str = "time_scale"; // seconds per raw timestamp tick
+pub const GYRO_SCALE: &str = "gyro_scale"; // rad/s per raw gyro LSB
+pub const ACCEL_SCALE: &str = "accel_scale"; // g per raw accel LSB
+
+const TYPE_INT: u8 = 0x01;
+const TYPE_FLOAT: u8 = 0x02;
+const TYPE_STR: u8 = 0x03;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl Value {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    // integers are widened so that scales written as whole numbers still read
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(x) => Some(*x),
+            Value::Int(x) => Some(*x as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    entries: Vec<(String, Value)>,
+}
+
+impl Metadata {
+    pub fn new() -> Metadata {
+        Metadata::default()
+    }
+
+    // replaces an existing entry with the same key
+    pub fn set(&mut self, key: &str, value: Value) {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some(e) => e.1 = value,
+            None => self.entries.push((key.to_owned(), value)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn merge(&mut self, other: Metadata) {
+        for (k, v) in other.entries {
+            self.set(&k, v);
+        }
+    }
+
+    pub fn gyro_scale(&self) -> Option<f64> {
+        self.get(GYRO_SCALE)?.as_float()
+    }
+
+    pub fn accel_scale(&self) -> Option<f64> {
+        self.get(ACCEL_SCALE)?.as_float()
+    }
+
+    pub fn time_scale(&self) -> Option<f64> {
+        self.get(TIME_SCALE)?.as_float()
+    }
+
+    pub fn sample_rate(&self) -> Option<f64> {
+        self.get(SAMPLE_RATE)?.as_float()
+    }
+
+    // serialized entries without the block id, None if a key or value is too long
+    pub(crate) fn encode_entries(entries: &[(String, Value)], out: &mut Vec<u8>) -> Option<()> {
+        out.push(u8::try_from(entries.len()).ok()?);
+        for (k, v) in entries {
+            out.push(u8::try_from(k.len()).ok()?);
+            out.extend_from_slice(k.as_bytes());
+            match v {
+                Value::Int(x) => {
+                    out.push(TYPE_INT);
+                    out.extend_from_slice(&x.to_le_bytes());
+                }
+                Value::Float(x) => {
+                    out.push(TYPE_FLOAT);
+                    out.extend_from_slice(&x.to_le_bytes());
+                }
+                Value::Str(x) => {
+                    out.push(TYPE_STR);
+                    out.extend_from_slice(&u16::try_from(x.len()).ok()?.to_le_bytes());
+                    out.extend_from_slice(x.as_bytes());
+                }
+            }
+        }
+        Some(())
+    }
+
+    pub(crate) fn entries(&self) -> &[(String, Value)] {
+        &self.entries
+    }
+
+    // parses the entries of a block, returns them and the number of bytes eaten
+    pub(crate) fn decode_entries(buf: &[u8]) -> Option<(Metadata, usize)> {
+        let mut meta = Metadata::new();
+        let n = *buf.first()?;
+        let mut pos = 1;
+        for _ in 0..n {
+            let klen = *buf.get(pos)? as usize;
+            let key = std::str::from_utf8(buf.get(pos + 1..pos + 1 + klen)?).ok()?;
+            pos += 1 + klen;
+            let ty = *buf.get(pos)?;
+            pos += 1;
+            let value = match ty {
+                TYPE_INT => Value::Int(i64::from_le_bytes(buf.get(pos..pos + 8)?.try_into().ok()?)),
+                TYPE_FLOAT => {
+                    Value::Float(f64::from_le_bytes(buf.get(pos..pos + 8)?.try_into().ok()?))
+                }
+                TYPE_STR => {
+                    let len = u16::from_le_bytes(buf.get(pos..pos + 2)?.try_into().ok()?) as usize;
+                    pos += 2;
+                    let s = std::str::from_utf8(buf.get(pos..pos + len)?).ok()?;
+                    pos += len;
+                    meta.set(key, Value::Str(s.to_owned()));
+                    continue;
+                }
+                _ => return None,
+            };
+            pos += 8;
+            meta.set(key, value);
+        }
+        Some((meta, pos))
+    }
+}
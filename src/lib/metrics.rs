@@ -0,0 +1,125 @@
+use crate::quat::Quat;
+
+// Angular error between an original and a reconstructed orientation stream.
+// Errors are computed in f64 from the rotation between the two quaternions,
+// so the fixed-point atan2/sqrt approximations do not leak into the numbers.
+// All angles are in degrees.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ErrorStats {
+    pub count: usize,
+    pub rmse: f64,
+    pub mean: f64,
+    pub max: f64,
+    pub max_index: usize, // sample index of the maximum within the whole stream
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub overall: ErrorStats,
+    pub axis_rmse: [f64; 3], // error rotation vector components, original frame
+    pub axis_max: [f64; 3],
+    pub blocks: Vec<ErrorStats>,
+    sorted: Vec<f32>,
+}
+
+impl Report {
+    // p in 0..=100, nearest rank
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = (p / 100.0 * self.sorted.len() as f64).ceil() as usize;
+        self.sorted[rank.clamp(1, self.sorted.len()) - 1] as f64
+    }
+}
+
+// error rotation vector from `a` to `b` in degrees
+pub fn error_rvec(a: &Quat, b: &Quat) -> [f64; 3] {
+    let f = |q: &Quat| [q.w, q.x, q.y, q.z].map(|x| x.to_float() as f64);
+    let [aw, ax, ay, az] = f(a);
+    let [bw, bx, by, bz] = f(b);
+    // conj(a) * b
+    let w = aw * bw + ax * bx + ay * by + az * bz;
+    let x = aw * bx - ax * bw - ay * bz + az * by;
+    let y = aw * by + ax * bz - ay * bw - az * bx;
+    let z = aw * bz - ax * by + ay * bx - az * bw;
+
+    let s = (x * x + y * y + z * z).sqrt();
+    if s == 0.0 {
+        return [0.0; 3];
+    }
+    // q and -q are the same rotation, take the short way
+    let angle = 2.0 * s.atan2(w.abs()) * w.signum();
+    [x, y, z].map(|c| (c / s * angle).to_degrees())
+}
+
+pub fn angular_error(a: &Quat, b: &Quat) -> f64 {
+    let [x, y, z] = error_rvec(a, b);
+    (x * x + y * y + z * z).sqrt()
+}
+
+fn stats(errors: &[f64], offset: usize) -> ErrorStats {
+    let n = errors.len();
+    if n == 0 {
+        return ErrorStats::default();
+    }
+    let (max_index, max) =
+        errors
+            .iter()
+            .enumerate()
+            .fold((0, 0.0), |m, (i, &e)| if e > m.1 { (i, e) } else { m });
+    ErrorStats {
+        count: n,
+        rmse: (errors.iter().map(|e| e * e).sum::<f64>() / n as f64).sqrt(),
+        mean: errors.iter().sum::<f64>() / n as f64,
+        max,
+        max_index: offset + max_index,
+    }
+}
+
+// compares the common prefix of both streams, `block_size` 0 skips the
+// per-block breakdown
+pub fn compare(original: &[Quat], decoded: &[Quat], block_size: usize) -> Report {
+    let rvecs: Vec<[f64; 3]> = original
+        .iter()
+        .zip(decoded)
+        .map(|(a, b)| error_rvec(a, b))
+        .collect();
+    let errors: Vec<f64> = rvecs
+        .iter()
+        .map(|[x, y, z]| (x * x + y * y + z * z).sqrt())
+        .collect();
+
+    let n = rvecs.len().max(1) as f64;
+    let mut axis_rmse = [0.0; 3];
+    let mut axis_max = [0.0f64; 3];
+    for v in &rvecs {
+        for i in 0..3 {
+            axis_rmse[i] += v[i] * v[i];
+            axis_max[i] = axis_max[i].max(v[i].abs());
+        }
+    }
+    let axis_rmse = axis_rmse.map(|x| (x / n).sqrt());
+
+    let blocks = if block_size > 0 {
+        errors
+            .chunks(block_size)
+            .enumerate()
+            .map(|(i, c)| stats(c, i * block_size))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let mut sorted: Vec<f32> = errors.iter().map(|&x| x as f32).collect();
+    sorted.sort_by(f32::total_cmp);
+
+    Report {
+        overall: stats(&errors, 0),
+        axis_rmse,
+        axis_max,
+        blocks,
+        sorted,
+    }
+}
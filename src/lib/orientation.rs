@@ -0,0 +1,166 @@
+use std::fmt::Display;
+use std::ops::Neg;
+
+use crate::quat::{Fix, Quat, RVec};
+
+// IMU axis remap in the Gyroflow notation, e.g. "YxZ": letter i names the IMU
+// axis that becomes camera axis i, lowercase letters flip its sign. Mirrored
+// remaps (an odd number of flips and swaps) are accepted as well, rotations
+// are then conjugated by the mirror, which flips their axes once more.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImuOrientation {
+    axes: [u8; 3],
+}
+
+impl ImuOrientation {
+    pub const IDENTITY: ImuOrientation = ImuOrientation { axes: *b"XYZ" };
+
+    pub fn from_bytes(axes: [u8; 3]) -> Option<ImuOrientation> {
+        let mut seen = [false; 3];
+        for a in axes {
+            let i = match a {
+                b'X' | b'x' => 0,
+                b'Y' | b'y' => 1,
+                b'Z' | b'z' => 2,
+                _ => return None,
+            };
+            if seen[i] {
+                return None;
+            }
+            seen[i] = true;
+        }
+        Some(ImuOrientation { axes })
+    }
+
+    pub fn parse(s: &str) -> Option<ImuOrientation> {
+        Self::from_bytes(s.as_bytes().try_into().ok()?)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 3] {
+        self.axes
+    }
+
+    // (source axis, sign) of every camera axis
+    fn source(&self, i: usize) -> (usize, i32) {
+        let a = self.axes[i];
+        let sign = if a.is_ascii_uppercase() { 1 } else { -1 };
+        ((a.to_ascii_lowercase() - b'x') as usize, sign)
+    }
+
+    pub fn to_matrix(&self) -> [[i32; 3]; 3] {
+        let mut m = [[0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            let (j, sign) = self.source(i);
+            row[j] = sign;
+        }
+        m
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        self.determinant() < 0
+    }
+
+    fn determinant(&self) -> i32 {
+        let m = self.to_matrix();
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // rotation taking IMU frame vectors to the camera frame; for a mirrored
+    // remap the rotation of the negated matrix, which maps rotations the same
+    pub fn to_quat(&self) -> Quat {
+        let det = self.determinant() as f64;
+        let m = self.to_matrix().map(|r| r.map(|x| x as f64 * det));
+        let tr = m[0][0] + m[1][1] + m[2][2];
+        let (w, x, y, z) = if tr > 0.0 {
+            let s = (tr + 1.0).sqrt() * 2.0;
+            (
+                0.25 * s,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            (
+                (m[2][1] - m[1][2]) / s,
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            (
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            (
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+            )
+        };
+        Quat::new(
+            Fix::from_float(w as f32),
+            Fix::from_float(x as f32),
+            Fix::from_float(y as f32),
+            Fix::from_float(z as f32),
+        )
+    }
+
+    pub fn remap<T: Copy + Neg<Output = T>>(&self, v: [T; 3]) -> [T; 3] {
+        [0, 1, 2].map(|i| {
+            let (j, sign) = self.source(i);
+            if sign > 0 {
+                v[j]
+            } else {
+                -v[j]
+            }
+        })
+    }
+
+    // saturates instead of overflowing on i16::MIN
+    pub fn remap_i16(&self, v: [i16; 3]) -> [i16; 3] {
+        [0, 1, 2].map(|i| {
+            let (j, sign) = self.source(i);
+            if sign > 0 {
+                v[j]
+            } else {
+                v[j].saturating_neg()
+            }
+        })
+    }
+
+    // rotation vectors are axial, a mirror flips them
+    pub fn remap_vec(&self, v: &RVec) -> RVec {
+        let [x, y, z] = self.remap_axial([v.x, v.y, v.z]);
+        RVec::new(x, y, z)
+    }
+
+    // same as r * q * r.conj() for r = self.to_quat(), but exact
+    pub fn remap_quat(&self, q: &Quat) -> Quat {
+        let [x, y, z] = self.remap_axial([q.x, q.y, q.z]);
+        Quat::new(q.w, x, y, z)
+    }
+
+    fn remap_axial<T: Copy + Neg<Output = T>>(&self, v: [T; 3]) -> [T; 3] {
+        let v = self.remap(v);
+        if self.is_mirrored() {
+            v.map(|x| -x)
+        } else {
+            v
+        }
+    }
+}
+
+impl Display for ImuOrientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(std::str::from_utf8(&self.axes).unwrap())
+    }
+}
@@ -1,73 +1,124 @@
-use crate::quat::{Fix, Quat, RVec};
+use crate::{
+    fix32::Precision,
+    quat::{Fix, Quat, Vec3},
+    scalar::Scalar,
+};
 
+// largest qp, the updates are shifted by it
+pub const MAX_QP: u8 = 31;
+
+// Generic over the number type so the quantization loop can run in float
+// for research, State<Fix> is the codec's.
 #[derive(Copy, Clone, Debug)]
-pub struct State {
-    pub q: Quat, // decoder's quat
-    pub v: RVec, // decoder's angular velocity
+pub struct State<T = Fix> {
+    pub q: Quat<T>, // decoder's quat
+    pub v: Vec3<T>, // decoder's angular velocity
 }
 
 #[derive(Copy, Clone, Debug)]
-pub struct QuantResult {
-    pub new_state: State,
+pub struct QuantResult<T = Fix> {
+    pub new_state: State<T>,
     pub bytes_put: usize,
-    pub max_ang_err: Fix,
+    pub max_ang_err: T,
+}
+
+// why quant_block_checked gave up
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QuantError {
+    InvalidQp,
+    OutOfSpace,
+    // quats[index] is far from unit length or its update would overflow
+    // the angular velocity
+    OutOfRange { index: usize },
 }
 
 #[derive(Copy, Clone, Debug)]
-pub struct DequantResult {
-    pub new_state: State,
+pub struct DequantResult<T = Fix> {
+    pub new_state: State<T>,
     pub quats_put: usize,
 }
 
-impl State {
-    pub fn new() -> State {
+impl<T: Scalar> Default for State<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Scalar> State<T> {
+    pub fn new() -> State<T> {
         State {
             q: Quat::default(),
-            v: RVec::default(),
+            v: Vec3::default(),
         }
     }
 
-    pub fn quant_block(self, quats: &[Quat], qp: u8, out: &mut [i8]) -> Option<QuantResult> {
+    pub fn quant_block(self, quats: &[Quat<T>], qp: u8, out: &mut [i8]) -> Option<QuantResult<T>> {
+        self.quant_block_by(
+            quats,
+            qp,
+            out,
+            |q| q.to_rvec(),
+            Quat::from_rvec,
+            |_, _, _| true,
+        )
+        .ok()
+    }
+
+    fn quant_block_by(
+        self,
+        quats: &[Quat<T>],
+        qp: u8,
+        out: &mut [i8],
+        to_rvec: impl Fn(&Quat<T>) -> Vec3<T>,
+        from_rvec: impl Fn(&Vec3<T>) -> Quat<T>,
+        in_range: impl Fn(&Vec3<T>, &Vec3<T>, [i8; 3]) -> bool,
+    ) -> Result<QuantResult<T>, QuantError> {
+        if qp > MAX_QP {
+            return Err(QuantError::InvalidQp);
+        }
         let mut bytes_put = 0;
-        let mut max_ang_err = Fix::from_i32(0);
+        let mut max_ang_err = T::ZERO;
         let mut new_state = self;
 
-        for i in 0..quats.len() {
+        for (index, &q) in quats.iter().enumerate() {
             // compute angular acceleration update
-            let q = quats[i];
             let q_update = new_state.q.conj() * q;
-            let mut v_update = q_update.to_rvec() - new_state.v;
+            let mut v_update = to_rvec(&q_update) - new_state.v;
 
             // quantize update
-            let mut sum = RVec::default();
+            let mut sum = Vec3::default();
             let mut correction_needed = true;
             while correction_needed {
                 let update_quanted = quant_update(v_update, qp, 127);
+                if !in_range(&new_state.v, &sum, update_quanted) {
+                    return Err(QuantError::OutOfRange { index });
+                }
                 let update_dequanted = dequant_update(update_quanted, qp);
 
-                sum = sum + update_dequanted;
-                v_update = v_update - update_dequanted;
+                sum += update_dequanted;
+                v_update -= update_dequanted;
 
                 correction_needed = is_saturated(update_quanted, 127);
 
                 if bytes_put + 3 > out.len() {
-                    return None;
-                }
-                for b in 0..3 {
-                    out[bytes_put + b] = update_quanted[b];
+                    return Err(QuantError::OutOfSpace);
                 }
+                out[bytes_put..bytes_put + 3].copy_from_slice(&update_quanted);
                 bytes_put += 3;
             }
-            
+
             // update state
-            new_state.v = new_state.v + sum;
-            new_state.q = (new_state.q * Quat::from_rvec(&new_state.v)).normalize_safe();
+            new_state.v += sum;
+            new_state.q = (new_state.q * from_rvec(&new_state.v)).normalize_safe();
 
             // update max quantization error
-            max_ang_err = (new_state.q.conj() * q).to_rvec().norm().max(max_ang_err);
+            let err = to_rvec(&(new_state.q.conj() * q)).norm();
+            if err > max_ang_err {
+                max_ang_err = err;
+            }
         }
 
-        Some(QuantResult {
+        Ok(QuantResult {
             new_state,
             bytes_put,
             max_ang_err,
@@ -75,14 +126,19 @@ impl State {
     }
 
     // use decompress_block instead
-    pub fn dequant_block(self, data: &[i8], qp: u8, out: &mut [Quat]) -> Option<DequantResult> {
+    pub fn dequant_block(
+        self,
+        data: &[i8],
+        qp: u8,
+        out: &mut [Quat<T>],
+    ) -> Option<DequantResult<T>> {
         let mut quats_put = 0;
         let mut new_state = self;
 
         for n in 0..data.len() / 3 {
             let i = 3 * n;
-            let upd = [data[i] as i8, data[i + 1] as i8, data[i + 2] as i8];
-            new_state.v = new_state.v + dequant_update(upd, qp);
+            let upd = [data[i], data[i + 1], data[i + 2]];
+            new_state.v += dequant_update(upd, qp);
 
             if !is_saturated(upd, 127) {
                 if quats_put >= out.len() {
@@ -100,24 +156,123 @@ impl State {
         })
     }
 
-    pub fn dequant_one(&mut self, data: &[i8], qp: u8) -> Option<Quat> {
-        let upd = [data[0] as i8, data[1] as i8, data[2] as i8];
-        self.v = self.v + dequant_update(upd, qp);
+    pub fn dequant_one(&mut self, data: &[i8], qp: u8) -> Option<Quat<T>> {
+        self.dequant_one_by(data, qp, Quat::from_rvec)
+    }
+
+    fn dequant_one_by(
+        &mut self,
+        data: &[i8],
+        qp: u8,
+        from_rvec: impl Fn(&Vec3<T>) -> Quat<T>,
+    ) -> Option<Quat<T>> {
+        let upd = [data[0], data[1], data[2]];
+        self.v += dequant_update(upd, qp);
 
         if !is_saturated(upd, 127) {
-            self.q = (self.q * Quat::from_rvec(&self.v)).normalize_safe();
-            return Some(self.q)
+            self.q = (self.q * from_rvec(&self.v)).normalize_safe();
+            return Some(self.q);
         }
-        return None
+        None
     }
 }
 
-fn quant_update(update: RVec, scale: u8, lim: i8) -> [i8; 3] {
-    let r = [update.x.to_raw(), update.y.to_raw(), update.z.to_raw()];
+impl State<Fix> {
+    // `precision` of the rotation vector conversions, decoders must use the same
+    pub fn quant_block_with(
+        self,
+        quats: &[Quat],
+        qp: u8,
+        precision: Precision,
+        out: &mut [i8],
+    ) -> Option<QuantResult> {
+        self.quant_block_by(
+            quats,
+            qp,
+            out,
+            |q| q.to_rvec_with(precision),
+            |v| Quat::from_rvec_with(v, precision),
+            |_, _, _| true,
+        )
+        .ok()
+    }
+
+    // Same output as quant_block_with, but stops at the first quaternion far
+    // from unit length or whose updates would overflow the angular velocity,
+    // where the plain loop panics (debug builds) or wraps (release builds).
+    // The angular velocity is checked the way the decoder adds it up, which
+    // keeps every other intermediate in range as well.
+    pub fn quant_block_checked(
+        self,
+        quats: &[Quat],
+        qp: u8,
+        precision: Precision,
+        out: &mut [i8],
+    ) -> Result<QuantResult, QuantError> {
+        if let Some(index) = quats.iter().position(|q| !unit_in_range(q)) {
+            return Err(QuantError::OutOfRange { index });
+        }
+        self.quant_block_by(
+            quats,
+            qp,
+            out,
+            |q| q.to_rvec_with(precision),
+            |v| Quat::from_rvec_with(v, precision),
+            |v, sum, upd| sum_in_range(v, sum, upd, qp),
+        )
+    }
+
+    pub fn dequant_one_with(&mut self, data: &[i8], qp: u8, precision: Precision) -> Option<Quat> {
+        self.dequant_one_by(data, qp, |v| Quat::from_rvec_with(v, precision))
+    }
+
+    // false for a state only a corrupt stream has: a quaternion far from unit
+    // length or an angular velocity whose norm overflows
+    pub fn in_range(&self) -> bool {
+        unit_in_range(&self.q) && theta2_in_range(self.v.x, self.v.y, self.v.z)
+    }
+
+    // same for the angular velocity after the update
+    pub fn update_in_range(&self, data: &[i8], qp: u8) -> bool {
+        sum_in_range(&self.v, &Vec3::default(), [data[0], data[1], data[2]], qp)
+    }
+}
+
+fn unit_in_range(q: &Quat) -> bool {
+    q.checked_norm()
+        .is_some_and(|n| n > Fix::from_float(0.5) && n < Fix::from_i32(2))
+}
+
+// v + sum + the dequantized update
+fn sum_in_range(v: &Vec3, sum: &Vec3, upd: [i8; 3], qp: u8) -> bool {
+    let d: Vec3 = dequant_update(upd, qp);
+    let add = |a: Fix, b: Fix, c: Fix| a.checked_add(b)?.checked_add(c);
+    match (
+        add(v.x, sum.x, d.x),
+        add(v.y, sum.y, d.y),
+        add(v.z, sum.z, d.z),
+    ) {
+        (Some(x), Some(y), Some(z)) => theta2_in_range(x, y, z),
+        _ => false,
+    }
+}
+
+fn theta2_in_range(x: Fix, y: Fix, z: Fix) -> bool {
+    [x, y, z]
+        .iter()
+        .try_fold(Fix::ZERO, |sum, &x| sum.checked_add(x.checked_mul(x)?))
+        .is_some()
+}
+
+// quantization happens on the Q27 raw values whatever the number type
+fn quant_update<T: Scalar>(update: Vec3<T>, scale: u8, lim: i8) -> [i8; 3] {
+    let r = [update.x, update.y, update.z].map(|x| x.to_fix().to_raw());
     let u = r.map(|r| (r >> scale) as i8);
 
     let check_ovf = |orig: i32, quant: i8| {
-        if quant as i32 == (orig >> scale) && quant.abs() <= lim {
+        // -128 passes as it always has, abs() wraps on it in release builds
+        // and changing that would change the bitstream
+        if quant as i32 == (orig >> scale) && quant.wrapping_abs() <= lim {
             quant
         } else {
             if orig < 0 {
@@ -135,14 +290,11 @@ fn quant_update(update: RVec, scale: u8, lim: i8) -> [i8; 3] {
     ]
 }
 
-fn dequant_update(update: [i8; 3], scale: u8) -> RVec {
-    RVec::new(
-        Fix::from_raw((update[0] as i32) << scale),
-        Fix::from_raw((update[1] as i32) << scale),
-        Fix::from_raw((update[2] as i32) << scale),
-    )
+fn dequant_update<T: Scalar>(update: [i8; 3], scale: u8) -> Vec3<T> {
+    let d = |u: i8| T::from_fix(Fix::from_raw((u as i32) << scale));
+    Vec3::new(d(update[0]), d(update[1]), d(update[2]))
 }
 
 fn is_saturated(v: [i8; 3], lim: i8) -> bool {
-    v[0].abs() == lim || v[1].abs() == lim || v[2].abs() == lim
+    v.iter().any(|x| x.wrapping_abs() == lim)
 }
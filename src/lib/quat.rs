@@ -1,50 +1,63 @@
-use std::ops::{Add, Mul, Neg, Sub};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
-use crate::fix32::Fix32;
+use crate::{
+    fix32::{Fix32, Precision},
+    scalar::Scalar,
+};
 
 pub type Fix = Fix32<27>;
-pub type RVec = Vec3<27>;
+pub type RVec = Vec3<Fix>;
 
+// Generic over the number type, Quat<Fix> is what the codec uses. Quat<Fix64<N>>
+// can carry integrators and encoder state at higher precision.
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Quat {
-    pub w: Fix,
-    pub x: Fix,
-    pub y: Fix,
-    pub z: Fix,
+pub struct Quat<T = Fix> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Default for Quat {
+impl<T: Scalar> Default for Quat<T> {
     fn default() -> Self {
         Self {
-            w: Fix::from_i32(1),
-            x: Fix::from_i32(0),
-            y: Fix::from_i32(0),
-            z: Fix::from_i32(0),
+            w: T::ONE,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
         }
     }
 }
 
-impl Quat {
-    pub fn new(w: Fix, x: Fix, y: Fix, z: Fix) -> Quat {
+impl<T: Scalar> Quat<T> {
+    pub fn new(w: T, x: T, y: T, z: T) -> Quat<T> {
         Quat { w, x, y, z }
     }
 
-    pub fn from_rvec(v: &RVec) -> Quat {
+    pub fn from_rvec(v: &Vec3<T>) -> Quat<T> {
+        Self::from_rvec_by(v, |x| x.sin(), |x| x.cos())
+    }
+
+    fn from_rvec_by(v: &Vec3<T>, sin: impl Fn(T) -> T, cos: impl Fn(T) -> T) -> Quat<T> {
+        // 16 ulp for fixed point
+        let tiny = (0..4).fold(T::MIN_POSITIVE, |x, _| x + x);
         let theta2 = v.x * v.x + v.y * v.y + v.z * v.z;
-        if theta2.to_raw() > 16 {
+        if theta2 > tiny {
             let theta = theta2.sqrt();
-            let half_theta = theta * Fix::from_float(0.5);
-            let k = half_theta.sin() / theta;
+            let half_theta = theta * T::from_f64(0.5);
+            let k = sin(half_theta) / theta;
             Quat {
-                w: half_theta.cos(),
+                w: cos(half_theta),
                 x: v.x * k,
                 y: v.y * k,
                 z: v.z * k,
             }
         } else {
-            let k = Fix::from_float(0.5);
+            let k = T::from_f64(0.5);
             Quat {
-                w: Fix::from_i32(1),
+                w: T::ONE,
                 x: v.x * k,
                 y: v.y * k,
                 z: v.z * k,
@@ -52,33 +65,38 @@ impl Quat {
         }
     }
 
-    pub fn to_rvec(&self) -> RVec {
+    pub fn to_rvec(&self) -> Vec3<T> {
+        self.rvec_by(|y, x| y.atan2(x))
+    }
+
+    fn rvec_by(&self, atan2: impl Fn(T, T) -> T) -> Vec3<T> {
+        let two = T::ONE + T::ONE;
         let sin_theta2 = self.x * self.x + self.y * self.y + self.z * self.z;
-        if sin_theta2.to_raw() <= 0 {
-            return RVec {
-                x: self.x * Fix::from_i32(2),
-                y: self.y * Fix::from_i32(2),
-                z: self.z * Fix::from_i32(2),
+        if sin_theta2 <= T::ZERO {
+            return Vec3 {
+                x: self.x * two,
+                y: self.y * two,
+                z: self.z * two,
             };
         }
 
         let sin_theta = sin_theta2.sqrt();
         let cos_theta = self.w;
-        let two_theta = Fix::from_i32(2)
-            * if cos_theta.to_raw() < 0 {
-                (-sin_theta).atan2(-cos_theta)
+        let two_theta = two
+            * if cos_theta < T::ZERO {
+                atan2(-sin_theta, -cos_theta)
             } else {
-                sin_theta.atan2(cos_theta)
+                atan2(sin_theta, cos_theta)
             };
         let k = two_theta / sin_theta;
-        RVec {
+        Vec3 {
             x: self.x * k,
             y: self.y * k,
             z: self.z * k,
         }
     }
 
-    pub fn conj(&self) -> Quat {
+    pub fn conj(&self) -> Quat<T> {
         Quat {
             w: self.w,
             x: -self.x,
@@ -87,45 +105,45 @@ impl Quat {
         }
     }
 
-    pub fn norm(&self) -> Fix {
+    pub fn norm(&self) -> T {
         (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
     }
 
-    pub fn normalize(&mut self) -> Quat {
+    pub fn normalize(&self) -> Quat<T> {
         let norm = self.norm();
         self.sdiv(norm)
     }
 
-    pub fn normalize_safe(&mut self) -> Quat {
+    pub fn normalize_safe(&self) -> Quat<T> {
         let norm = self.norm();
-        if norm.to_raw() == 0 {
+        if norm == T::ZERO {
             return Quat {
-                w: Fix::from_raw(0),
-                x: Fix::from_raw(0),
-                y: Fix::from_raw(0),
-                z: Fix::from_raw(0),
+                w: T::ZERO,
+                x: T::ZERO,
+                y: T::ZERO,
+                z: T::ZERO,
             };
         }
         self.sdiv(norm)
     }
 
-    pub fn rotate_point(&self, p: &RVec) -> RVec {
+    pub fn rotate_point(&self, p: &Vec3<T>) -> Vec3<T> {
         let qq = (*self)
             * Quat {
-                w: Fix::from_raw(0),
+                w: T::ZERO,
                 x: p.x,
                 y: p.y,
                 z: p.z,
             }
             * self.conj();
-        RVec {
+        Vec3 {
             x: qq.x,
             y: qq.y,
             z: qq.z,
         }
     }
 
-    pub fn smul(&self, x: Fix) -> Quat {
+    pub fn smul(&self, x: T) -> Quat<T> {
         Quat {
             w: self.w * x,
             x: self.x * x,
@@ -134,7 +152,7 @@ impl Quat {
         }
     }
 
-    pub fn sdiv(&self, x: Fix) -> Quat {
+    pub fn sdiv(&self, x: T) -> Quat<T> {
         Quat {
             w: self.w / x,
             x: self.x / x,
@@ -142,10 +160,322 @@ impl Quat {
             z: self.z / x,
         }
     }
+
+    pub fn dot(&self, rhs: &Quat<T>) -> T {
+        self.w * rhs.w + self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    // the zero quaternion has none
+    pub fn inverse(&self) -> Quat<T> {
+        self.conj().sdiv(self.dot(self))
+    }
+
+    // angle of the rotation taking self to rhs, in [0, pi]
+    pub fn angle_to(&self, rhs: &Quat<T>) -> T {
+        let d = self.conj() * *rhs;
+        let s = (d.x * d.x + d.y * d.y + d.z * d.z).sqrt();
+        let w = if d.w < T::ZERO { -d.w } else { d.w };
+        (T::ONE + T::ONE) * s.atan2(w)
+    }
+
+    // the axis does not need to be normalized but must not be zero
+    pub fn from_axis_angle(axis: &Vec3<T>, angle: T) -> Quat<T> {
+        let half = angle * T::from_f64(0.5);
+        let k = half.sin() / axis.norm();
+        Quat {
+            w: half.cos(),
+            x: axis.x * k,
+            y: axis.y * k,
+            z: axis.z * k,
+        }
+    }
+
+    // unit axis and angle in [0, pi], the x axis for the identity
+    pub fn to_axis_angle(&self) -> (Vec3<T>, T) {
+        let q = if self.w < T::ZERO { -*self } else { *self };
+        let s = (q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        if s == T::ZERO {
+            return (Vec3::new(T::ONE, T::ZERO, T::ZERO), T::ZERO);
+        }
+        let axis = Vec3::new(q.x / s, q.y / s, q.z / s);
+        (axis, (T::ONE + T::ONE) * s.atan2(q.w))
+    }
+
+    // Logarithm and exponential of unit quaternions: log gives the pure
+    // quaternion (0, angle/2 * axis), exp takes the vector part of a pure
+    // quaternion back (w is ignored).
+
+    pub fn log(&self) -> Quat<T> {
+        let v = self.rvec_by(atan2).smul(T::from_f64(0.5));
+        Quat::new(T::ZERO, v.x, v.y, v.z)
+    }
+
+    pub fn exp(&self) -> Quat<T> {
+        let two = T::ONE + T::ONE;
+        Quat::from_rvec(&Vec3::new(self.x * two, self.y * two, self.z * two))
+    }
+
+    // Interpolation between unit quaternions along the shorter arc, t = 0
+    // gives self and t = 1 gives rhs (or -rhs).
+
+    // constant angular velocity
+    pub fn slerp(&self, rhs: &Quat<T>, t: T) -> Quat<T> {
+        let d = self.conj() * *rhs;
+        *self * Quat::from_rvec(&d.rvec_by(atan2).smul(t))
+    }
+
+    // cheaper, the angular velocity is not constant
+    pub fn nlerp(&self, rhs: &Quat<T>, t: T) -> Quat<T> {
+        let rhs = if self.dot(rhs) < T::ZERO { -*rhs } else { *rhs };
+        (self.smul(T::ONE - t) + rhs.smul(t)).normalize_safe()
+    }
+}
+
+// Fix32::atan2 keeps the sign of the original encoder for y < 0 < x, which
+// is wrong but part of the bitstream (to_rvec of quaternions with w < 0).
+// Everything outside the codec goes through this one.
+fn atan2<T: Scalar>(y: T, x: T) -> T {
+    if y < T::ZERO {
+        -(-y).atan2(x)
+    } else {
+        y.atan2(x)
+    }
+}
+
+// Tait-Bryan rotation orders. The angles rotate about the axes in the named
+// order, each about the axis of the already rotated frame (intrinsic), so
+// ZYX is yaw, pitch, roll.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    // axis indices and +1 for the cyclic orders, -1 for the others
+    fn axes(self) -> ([usize; 3], i32) {
+        match self {
+            EulerOrder::XYZ => ([0, 1, 2], 1),
+            EulerOrder::YZX => ([1, 2, 0], 1),
+            EulerOrder::ZXY => ([2, 0, 1], 1),
+            EulerOrder::XZY => ([0, 2, 1], -1),
+            EulerOrder::YXZ => ([1, 0, 2], -1),
+            EulerOrder::ZYX => ([2, 1, 0], -1),
+        }
+    }
+}
+
+// Rotation matrices are row major and rotate column vectors like
+// rotate_point.
+impl<T: Scalar> Quat<T> {
+    pub fn to_matrix(&self) -> [[T; 3]; 3] {
+        let two = T::ONE + T::ONE;
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        [
+            [
+                T::ONE - two * (y * y + z * z),
+                two * (x * y - w * z),
+                two * (x * z + w * y),
+            ],
+            [
+                two * (x * y + w * z),
+                T::ONE - two * (x * x + z * z),
+                two * (y * z - w * x),
+            ],
+            [
+                two * (x * z - w * y),
+                two * (y * z + w * x),
+                T::ONE - two * (x * x + y * y),
+            ],
+        ]
+    }
+
+    // the matrix has to be a rotation, the result is normalized
+    pub fn from_matrix(m: &[[T; 3]; 3]) -> Quat<T> {
+        // divide by the largest component for accuracy
+        let (one, four) = (T::ONE, T::from_f64(4.0));
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > T::ZERO {
+            let s = (trace + one).sqrt() * (one + one);
+            Quat::new(
+                s / four,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (one + m[0][0] - m[1][1] - m[2][2]).sqrt() * (one + one);
+            Quat::new(
+                (m[2][1] - m[1][2]) / s,
+                s / four,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (one + m[1][1] - m[0][0] - m[2][2]).sqrt() * (one + one);
+            Quat::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / four,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (one + m[2][2] - m[0][0] - m[1][1]).sqrt() * (one + one);
+            Quat::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / four,
+            )
+        };
+        q.normalize_safe()
+    }
+
+    pub fn from_euler(order: EulerOrder, angles: [T; 3]) -> Quat<T> {
+        let (axes, _) = order.axes();
+        let rot = |axis: usize, angle: T| {
+            let half = angle * T::from_f64(0.5);
+            let mut v = [T::ZERO; 3];
+            v[axis] = half.sin();
+            Quat::new(half.cos(), v[0], v[1], v[2])
+        };
+        rot(axes[0], angles[0]) * rot(axes[1], angles[1]) * rot(axes[2], angles[2])
+    }
+
+    // cos of the middle angle below which to_euler treats it as gimbal lock,
+    // 128 EPSILON: 9.5e-7 for Q27, 1.2e-7 for Q30 and 2.8e-14 for f64. The
+    // fixed point cos is computed from squares that round to 0 below
+    // sqrt(EPSILON), so there it is either 0 or well above the threshold.
+    pub fn gimbal_lock_threshold() -> T {
+        // doubled up, 128 does not fit every fixed point type
+        (0..7).fold(T::EPSILON, |x, _| x + x)
+    }
+
+    // The middle angle is in [-pi/2, pi/2], the others in [-pi, pi]. At
+    // gimbal lock (its cos below gimbal_lock_threshold) only the sum or
+    // difference of the outer angles is defined, the third angle is then
+    // returned as 0.
+    pub fn to_euler(&self, order: EulerOrder) -> [T; 3] {
+        let ([i, j, k], sign) = order.axes();
+        let m = self.to_matrix();
+        let e = |x: T| if sign < 0 { -x } else { x };
+
+        // cos of the middle angle from the other two entries of the row, an
+        // asin of m[i][k] would lose half the precision near gimbal lock
+        let c = (m[i][i] * m[i][i] + m[i][j] * m[i][j]).sqrt();
+        let middle = atan2(e(m[i][k]), c);
+        if c < Self::gimbal_lock_threshold() {
+            return [atan2(e(m[k][j]), m[j][j]), middle, T::ZERO];
+        }
+
+        // the third angle is taken with the first one undone, so the angles
+        // stay consistent when rounding makes the first one inaccurate
+        let first = atan2(e(-m[j][k]), m[k][k]);
+        let (s1, c1) = (first.sin(), first.cos());
+        let third = atan2(
+            e(c1 * m[j][i] + e(s1 * m[k][i])),
+            c1 * m[j][j] + e(s1 * m[k][j]),
+        );
+        [first, middle, third]
+    }
+}
+
+impl<T: Copy> Quat<T> {
+    // converts every component, e.g. q.map(|x| x.to_fix64::<50>())
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Quat<U> {
+        Quat {
+            w: f(self.w),
+            x: f(self.x),
+            y: f(self.y),
+            z: f(self.z),
+        }
+    }
 }
 
-impl Add for &Quat {
-    type Output = Quat;
+impl<const N: usize> Quat<Fix32<N>> {
+    pub fn from_rvec_with(v: &Vec3<Fix32<N>>, precision: Precision) -> Self {
+        Self::from_rvec_by(v, |x| x.sin_with(precision), |x| x.cos_with(precision))
+    }
+
+    pub fn to_rvec_with(&self, precision: Precision) -> Vec3<Fix32<N>> {
+        self.rvec_by(|y, x| y.atan2_with(x, precision))
+    }
+
+    // Overflow-detecting variants, None if any intermediate value does not
+    // fit or on division by zero. Results are the same as the operators
+    // otherwise.
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(Quat {
+            w: self.w.checked_add(rhs.w)?,
+            x: self.x.checked_add(rhs.x)?,
+            y: self.y.checked_add(rhs.y)?,
+            z: self.z.checked_add(rhs.z)?,
+        })
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let m = |a: Fix32<N>, b: Fix32<N>| a.checked_mul(b);
+        let (a, b) = (self, rhs);
+        Some(Quat {
+            w: m(a.w, b.w)?
+                .checked_sub(m(a.x, b.x)?)?
+                .checked_sub(m(a.y, b.y)?)?
+                .checked_sub(m(a.z, b.z)?)?,
+            x: m(a.w, b.x)?
+                .checked_add(m(a.x, b.w)?)?
+                .checked_add(m(a.y, b.z)?)?
+                .checked_sub(m(a.z, b.y)?)?,
+            y: m(a.w, b.y)?
+                .checked_sub(m(a.x, b.z)?)?
+                .checked_add(m(a.y, b.w)?)?
+                .checked_add(m(a.z, b.x)?)?,
+            z: m(a.w, b.z)?
+                .checked_add(m(a.x, b.y)?)?
+                .checked_sub(m(a.y, b.x)?)?
+                .checked_add(m(a.z, b.w)?)?,
+        })
+    }
+
+    pub fn checked_smul(&self, x: Fix32<N>) -> Option<Self> {
+        Some(Quat {
+            w: self.w.checked_mul(x)?,
+            x: self.x.checked_mul(x)?,
+            y: self.y.checked_mul(x)?,
+            z: self.z.checked_mul(x)?,
+        })
+    }
+
+    pub fn checked_sdiv(&self, x: Fix32<N>) -> Option<Self> {
+        Some(Quat {
+            w: self.w.checked_div(x)?,
+            x: self.x.checked_div(x)?,
+            y: self.y.checked_div(x)?,
+            z: self.z.checked_div(x)?,
+        })
+    }
+
+    pub fn checked_norm(&self) -> Option<Fix32<N>> {
+        let sq = |a: Fix32<N>| a.checked_mul(a);
+        let n2 = sq(self.w)?
+            .checked_add(sq(self.x)?)?
+            .checked_add(sq(self.y)?)?
+            .checked_add(sq(self.z)?)?;
+        Some(n2.sqrt())
+    }
+
+    // None for a zero quaternion too
+    pub fn checked_normalize(&self) -> Option<Self> {
+        self.checked_sdiv(self.checked_norm()?)
+    }
+}
+
+impl<T: Scalar> Add for &Quat<T> {
+    type Output = Quat<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
         Quat {
@@ -157,8 +487,8 @@ impl Add for &Quat {
     }
 }
 
-impl Mul for &Quat {
-    type Output = Quat;
+impl<T: Scalar> Mul for &Quat<T> {
+    type Output = Quat<T>;
 
     fn mul(self, rhs: Self) -> Self::Output {
         Quat {
@@ -170,35 +500,70 @@ impl Mul for &Quat {
     }
 }
 
-impl Add for Quat {
-    type Output = Quat;
+impl<T: Scalar> Sub for &Quat<T> {
+    type Output = Quat<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Quat {
+            w: self.w - rhs.w,
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: Scalar> Add for Quat<T> {
+    type Output = Quat<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        &self + &rhs
+        Add::add(&self, &rhs)
     }
 }
 
-impl Mul for Quat {
-    type Output = Quat;
+impl<T: Scalar> Mul for Quat<T> {
+    type Output = Quat<T>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        &self * &rhs
+        Mul::mul(&self, &rhs)
+    }
+}
+
+impl<T: Scalar> Sub for Quat<T> {
+    type Output = Quat<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Sub::sub(&self, &rhs)
+    }
+}
+
+// the same rotation
+impl<T: Scalar> Neg for Quat<T> {
+    type Output = Quat<T>;
+
+    fn neg(self) -> Self::Output {
+        Quat {
+            w: -self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
     }
 }
 
 #[derive(Copy, Clone, Debug)]
-pub struct Vec3<const N: usize> {
-    pub x: Fix32<N>,
-    pub y: Fix32<N>,
-    pub z: Fix32<N>,
+pub struct Vec3<T = Fix> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl<const N: usize> Vec3<N> {
-    pub fn new(x: Fix32<N>, y: Fix32<N>, z: Fix32<N>) -> Vec3<N> {
+impl<T: Scalar> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Vec3<T> {
         Vec3 { x, y, z }
     }
 
-    pub fn smul(&self, k: Fix32<N>) -> Vec3<N> {
+    pub fn smul(&self, k: T) -> Vec3<T> {
         Vec3 {
             x: self.x * k,
             y: self.y * k,
@@ -206,7 +571,7 @@ impl<const N: usize> Vec3<N> {
         }
     }
 
-    pub fn sdiv(&self, k: Fix32<N>) -> Vec3<N> {
+    pub fn sdiv(&self, k: T) -> Vec3<T> {
         Vec3 {
             x: self.x / k,
             y: self.y / k,
@@ -214,17 +579,60 @@ impl<const N: usize> Vec3<N> {
         }
     }
 
-    pub fn norm(&self) -> Fix32<N> {
+    pub fn norm(&self) -> T {
         (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
     }
 
-    pub fn normalized(&self) -> Vec3<N> {
+    pub fn normalized(&self) -> Vec3<T> {
         self.sdiv(self.norm())
     }
+
+    // zero for a zero vector
+    pub fn normalized_safe(&self) -> Vec3<T> {
+        let norm = self.norm();
+        if norm == T::ZERO {
+            return Vec3::default();
+        }
+        self.sdiv(norm)
+    }
+
+    pub fn dot(&self, rhs: &Vec3<T>) -> T {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(&self, rhs: &Vec3<T>) -> Vec3<T> {
+        Vec3 {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+
+    // largest absolute component, cheaper than norm
+    pub fn abs_max(&self) -> T {
+        let abs = |a: T| if a < T::ZERO { -a } else { a };
+        let (x, y, z) = (abs(self.x), abs(self.y), abs(self.z));
+        let m = if x > y { x } else { y };
+        if m > z {
+            m
+        } else {
+            z
+        }
+    }
 }
 
-impl<const N: usize> Add for Vec3<N> {
-    type Output = Vec3<N>;
+impl<T: Copy> Vec3<T> {
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Vec3<U> {
+        Vec3 {
+            x: f(self.x),
+            y: f(self.y),
+            z: f(self.z),
+        }
+    }
+}
+
+impl<T: Scalar> Add for Vec3<T> {
+    type Output = Vec3<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
         Vec3 {
@@ -235,8 +643,8 @@ impl<const N: usize> Add for Vec3<N> {
     }
 }
 
-impl<const N: usize> Sub for Vec3<N> {
-    type Output = Vec3<N>;
+impl<T: Scalar> Sub for Vec3<T> {
+    type Output = Vec3<T>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         Vec3 {
@@ -247,8 +655,48 @@ impl<const N: usize> Sub for Vec3<N> {
     }
 }
 
-impl<const N: usize> Neg for Vec3<N> {
-    type Output = Vec3<N>;
+impl<T: Scalar> Mul<T> for Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.smul(rhs)
+    }
+}
+
+impl<T: Scalar> Div<T> for Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.sdiv(rhs)
+    }
+}
+
+impl<T: Scalar> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Scalar> MulAssign<T> for Vec3<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        *self = self.smul(rhs);
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Vec3<T> {
+    fn div_assign(&mut self, rhs: T) {
+        *self = self.sdiv(rhs);
+    }
+}
+
+impl<T: Scalar> Neg for Vec3<T> {
+    type Output = Vec3<T>;
 
     fn neg(self) -> Self::Output {
         Vec3 {
@@ -259,12 +707,49 @@ impl<const N: usize> Neg for Vec3<N> {
     }
 }
 
-impl<const N: usize> Default for Vec3<N> {
+impl<T: Scalar> Default for Vec3<T> {
     fn default() -> Self {
         Self {
-            x: Fix32::<N>::from_i32(0),
-            y: Fix32::<N>::from_i32(0),
-            z: Fix32::<N>::from_i32(0),
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+        }
+    }
+}
+
+// by axis, 0 is x, 1 is y and 2 is z
+impl<T> Index<usize> for Vec3<T> {
+    type Output = T;
+
+    fn index(&self, axis: usize) -> &T {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("axis {} out of range", axis),
         }
     }
 }
+
+impl<T> IndexMut<usize> for Vec3<T> {
+    fn index_mut(&mut self, axis: usize) -> &mut T {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("axis {} out of range", axis),
+        }
+    }
+}
+
+impl<T> From<[T; 3]> for Vec3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl<T> From<Vec3<T>> for [T; 3] {
+    fn from(v: Vec3<T>) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
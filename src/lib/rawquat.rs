@@ -0,0 +1,94 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::quat::{Fix, Quat};
+
+// .rawquat files: an optional 8 byte header followed by one record per
+// quaternion of four int32_le values w, x, y, z in fixed point.
+//
+// header
+// size     content          description
+// 4        RAWQ             magic
+// 1        0x01             header version
+// 1        (uint8)          fractional bits of the values (27 for Quat)
+// 2        0x0000           reserved
+//
+// Files without the header hold Q27 values. The magic read as a Q27 number
+// is about 10.2, which no unit quaternion component can be, so both kinds
+// are told apart by the first four bytes.
+
+pub const MAGIC: &[u8; 4] = b"RAWQ";
+pub const HEADER_SIZE: usize = 8;
+const HEADER_VERSION: u8 = 0x01;
+const Q_FORMAT: u8 = 27;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// rescale a raw value from `q` fractional bits to Q27, rounding to nearest
+fn to_q27(v: i32, q: u8) -> io::Result<i32> {
+    let v = v as i64;
+    let r = match q.cmp(&Q_FORMAT) {
+        std::cmp::Ordering::Equal => v,
+        std::cmp::Ordering::Greater => {
+            let s = q - Q_FORMAT;
+            (v + (1 << (s - 1))) >> s
+        }
+        std::cmp::Ordering::Less => v << (Q_FORMAT - q),
+    };
+    i32::try_from(r).map_err(|_| invalid("value out of Q27 range"))
+}
+
+pub fn decode(buf: &[u8]) -> io::Result<Vec<Quat>> {
+    let (q, data) = if buf.starts_with(MAGIC) {
+        if buf.len() < HEADER_SIZE {
+            return Err(invalid("truncated rawquat header"));
+        }
+        if buf[4] != HEADER_VERSION {
+            return Err(invalid("unsupported rawquat header version"));
+        }
+        if buf[5] > 31 {
+            return Err(invalid("invalid rawquat Q-format"));
+        }
+        (buf[5], &buf[HEADER_SIZE..])
+    } else {
+        (Q_FORMAT, buf)
+    };
+    if data.len() % 16 != 0 {
+        return Err(invalid("rawquat size is not a multiple of 16 bytes"));
+    }
+
+    data.chunks_exact(16)
+        .map(|r| {
+            let c = |i: usize| -> io::Result<Fix> {
+                let v = i32::from_le_bytes(r[4 * i..4 * i + 4].try_into().unwrap());
+                Ok(Fix::from_raw(to_q27(v, q)?))
+            };
+            Ok(Quat::new(c(0)?, c(1)?, c(2)?, c(3)?))
+        })
+        .collect()
+}
+
+pub fn encode(quats: &[Quat], header: bool) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(HEADER_SIZE + 16 * quats.len());
+    if header {
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&[HEADER_VERSION, Q_FORMAT, 0, 0]);
+    }
+    for q in quats {
+        for x in [q.w, q.x, q.y, q.z] {
+            buf.extend_from_slice(&x.to_raw().to_le_bytes());
+        }
+    }
+    buf
+}
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<Quat>> {
+    decode(&fs::read(path)?)
+}
+
+pub fn write_file<P: AsRef<Path>>(path: P, quats: &[Quat], header: bool) -> io::Result<()> {
+    fs::write(path, encode(quats, header))
+}
@@ -0,0 +1,164 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{fix32::Fix32, fix64::Fix64};
+
+// Number type of Quat, Vec3 and the quantizer State. The methods mirror the
+// inherent ones of the fixed point types, so Quat<Fix> computes bit for bit
+// what it did before it was generic. The float implementations run the same
+// code for research and comparisons, they are not bit exact across targets.
+pub trait Scalar:
+    Copy
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    // smallest positive value
+    const MIN_POSITIVE: Self;
+    // difference between 1 and the next larger value
+    const EPSILON: Self;
+
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(&self) -> f64;
+    fn sqrt(&self) -> Self;
+    fn sin(&self) -> Self;
+    fn cos(&self) -> Self;
+    fn atan2(&self, x: Self) -> Self;
+
+    // to and from the Q27 units the bitstream quantizes in, rounding and
+    // saturating
+    fn to_fix(&self) -> Fix32<27>;
+    fn from_fix(x: Fix32<27>) -> Self;
+}
+
+impl<const N: usize> Scalar for Fix32<N> {
+    const ZERO: Self = Fix32::from_raw(0);
+    const ONE: Self = Fix32::from_raw(1 << N);
+    const MIN_POSITIVE: Self = Fix32::from_raw(1);
+    const EPSILON: Self = Fix32::from_raw(1);
+
+    fn from_f64(x: f64) -> Self {
+        Fix32::from_float(x as f32)
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.to_raw() as f64 / Self::MULT as f64
+    }
+
+    fn sqrt(&self) -> Self {
+        Fix32::sqrt(self)
+    }
+
+    fn sin(&self) -> Self {
+        Fix32::sin(self)
+    }
+
+    fn cos(&self) -> Self {
+        Fix32::cos(self)
+    }
+
+    fn atan2(&self, x: Self) -> Self {
+        Fix32::atan2(self, x)
+    }
+
+    fn to_fix(&self) -> Fix32<27> {
+        self.convert()
+    }
+
+    fn from_fix(x: Fix32<27>) -> Self {
+        x.convert()
+    }
+}
+
+impl<const N: usize> Scalar for Fix64<N> {
+    const ZERO: Self = Fix64::from_raw(0);
+    const ONE: Self = Fix64::from_raw(1 << N);
+    const MIN_POSITIVE: Self = Fix64::from_raw(1);
+    const EPSILON: Self = Fix64::from_raw(1);
+
+    fn from_f64(x: f64) -> Self {
+        Fix64::from_float(x)
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.to_float()
+    }
+
+    fn sqrt(&self) -> Self {
+        Fix64::sqrt(self)
+    }
+
+    fn sin(&self) -> Self {
+        Fix64::sin(self)
+    }
+
+    fn cos(&self) -> Self {
+        Fix64::cos(self)
+    }
+
+    fn atan2(&self, x: Self) -> Self {
+        Fix64::atan2(self, x)
+    }
+
+    fn to_fix(&self) -> Fix32<27> {
+        self.to_fix32()
+    }
+
+    fn from_fix(x: Fix32<27>) -> Self {
+        x.to_fix64()
+    }
+}
+
+macro_rules! float_scalar {
+    ($t:ident) => {
+        impl Scalar for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const MIN_POSITIVE: Self = $t::MIN_POSITIVE;
+            const EPSILON: Self = $t::EPSILON;
+
+            fn from_f64(x: f64) -> Self {
+                x as $t
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+
+            fn sqrt(&self) -> Self {
+                $t::sqrt(*self)
+            }
+
+            fn sin(&self) -> Self {
+                $t::sin(*self)
+            }
+
+            fn cos(&self) -> Self {
+                $t::cos(*self)
+            }
+
+            fn atan2(&self, x: Self) -> Self {
+                $t::atan2(*self, x)
+            }
+
+            fn to_fix(&self) -> Fix32<27> {
+                let raw = (*self as f64 * (1 << 27) as f64).round();
+                Fix32::from_raw(raw as i32)
+            }
+
+            fn from_fix(x: Fix32<27>) -> Self {
+                (x.to_raw() as f64 / (1 << 27) as f64) as $t
+            }
+        }
+    };
+}
+
+float_scalar!(f32);
+float_scalar!(f64);
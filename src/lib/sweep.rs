@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+
+use crate::{
+    esplog::{self, Error, Writer},
+    metrics,
+    quat::Quat,
+};
+
+// Rate-distortion sweep: encodes the same orientation stream for every
+// combination of qp and gyro block size and measures size against error.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SweepPoint {
+    pub qp: u8,
+    pub block_size: usize,
+    pub bytes: usize, // whole gyro-only EspLog file
+    pub bits_per_sample: f64,
+    pub rmse: f64, // deg
+    pub max: f64,  // deg
+}
+
+pub fn encode_point(quats: &[Quat], qp: u8, block_size: usize) -> Result<SweepPoint, Error> {
+    let mut w = Writer::new(qp)?;
+    for chunk in quats.chunks(block_size.max(1)) {
+        w.gyro_data(chunk)?;
+    }
+    let buf = w.finish();
+    let log = esplog::decode(&buf)?;
+    let report = metrics::compare(quats, &log.quats, 0);
+    Ok(SweepPoint {
+        qp,
+        block_size,
+        bytes: buf.len(),
+        bits_per_sample: buf.len() as f64 * 8.0 / quats.len().max(1) as f64,
+        rmse: report.overall.rmse,
+        max: report.overall.max,
+    })
+}
+
+pub fn sweep(quats: &[Quat], qps: &[u8], block_sizes: &[usize]) -> Result<Vec<SweepPoint>, Error> {
+    let mut points = vec![];
+    for &block_size in block_sizes {
+        for &qp in qps {
+            points.push(encode_point(quats, qp, block_size)?);
+        }
+    }
+    Ok(points)
+}
+
+pub fn write_csv<W: Write>(mut w: W, points: &[SweepPoint]) -> io::Result<()> {
+    writeln!(w, "qp,block_size,bytes,bits_per_sample,rmse_deg,max_deg")?;
+    for p in points {
+        writeln!(
+            w,
+            "{},{},{},{:.4},{:.6},{:.6}",
+            p.qp, p.block_size, p.bytes, p.bits_per_sample, p.rmse, p.max
+        )?;
+    }
+    Ok(())
+}
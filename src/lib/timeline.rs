@@ -0,0 +1,102 @@
+use crate::esplog::Log;
+
+// Absolute sample times reconstructed from time blocks. Every time block
+// closes the span of samples decoded since the previous one, the samples of
+// a span are spread evenly over its `dt` so that the last one lands on the
+// time block. Samples after the last time block continue at the mean rate.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timestamps {
+    pub us: Vec<i64>,
+    pub sample_rate: f64, // Hz
+    pub jitter_us: f64,   // std dev of the sample period across time blocks
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timeline {
+    pub quats: Timestamps,
+    pub accels: Timestamps,
+}
+
+impl Timeline {
+    // None if the log has gyro samples but neither time blocks nor a sample
+    // rate in its metadata
+    pub fn new(log: &Log) -> Option<Timeline> {
+        let offset = log.time_offset_us.unwrap_or(0) as i64;
+        let fallback = log.metadata.sample_rate().map(|r| 1e6 / r);
+
+        let marks: Vec<(u32, usize)> = log.time.iter().map(|t| (t.dt_us, t.quats)).collect();
+        let quats = if log.quats.is_empty() {
+            Timestamps::default()
+        } else {
+            spread(&marks, log.quats.len(), offset, fallback)?
+        };
+
+        let marks: Vec<(u32, usize)> = log.time.iter().map(|t| (t.dt_us, t.accels)).collect();
+        let gyro_period = Some(1e6 / quats.sample_rate).filter(|x| x.is_finite());
+        let accels = if log.accels.is_empty() {
+            Timestamps::default()
+        } else {
+            spread(&marks, log.accels.len(), offset, gyro_period)?
+        };
+
+        Some(Timeline { quats, accels })
+    }
+}
+
+impl Log {
+    pub fn timeline(&self) -> Option<Timeline> {
+        Timeline::new(self)
+    }
+}
+
+// `marks` are (dt, samples before the time block) pairs
+fn spread(
+    marks: &[(u32, usize)],
+    len: usize,
+    offset: i64,
+    fallback_period: Option<f64>,
+) -> Option<Timestamps> {
+    let mut us = Vec::with_capacity(len);
+    let mut t = 0i64;
+    let mut periods = vec![];
+    for &(dt, idx) in marks {
+        let idx = idx.min(len);
+        let n = idx - us.len();
+        for j in 1..=n {
+            us.push(offset + t + dt as i64 * j as i64 / n as i64);
+        }
+        if n > 0 {
+            periods.push((dt as f64 / n as f64, n));
+        }
+        t += dt as i64;
+    }
+
+    let covered: usize = periods.iter().map(|x| x.1).sum();
+    let period = if covered > 0 {
+        periods.iter().map(|(p, n)| p * *n as f64).sum::<f64>() / covered as f64
+    } else {
+        fallback_period?
+    };
+    let jitter_us = if covered > 0 {
+        (periods
+            .iter()
+            .map(|(p, n)| (p - period) * (p - period) * *n as f64)
+            .sum::<f64>()
+            / covered as f64)
+            .sqrt()
+    } else {
+        0.0
+    };
+
+    let tail = us.len();
+    for j in 1..=len - tail {
+        us.push(offset + t + (period * j as f64).round() as i64);
+    }
+
+    Some(Timestamps {
+        us,
+        sample_rate: 1e6 / period,
+        jitter_us,
+    })
+}
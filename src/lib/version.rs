@@ -0,0 +1,104 @@
+use crate::esplog::{
+    BLK_ACCEL_DATA, BLK_ACCEL_SETUP, BLK_GYRO_DATA, BLK_GYRO_SETUP, BLK_HOST_TIME,
+    BLK_IMU_ORIENTATION, BLK_KEYFRAME, BLK_METADATA, BLK_TIME, BLK_TIME_OFFSET,
+};
+
+// Registry of the container format versions and gyro compression algorithm
+// revisions this library understands. New versions are appended here, old
+// entries are never removed so that old files keep decoding.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatVersion {
+    pub version: u8,           // header byte following the magic
+    pub blocks: &'static [u8], // block ids defined by this version
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GyroRevision {
+    pub revision: u8,
+    pub description: &'static str,
+}
+
+pub const FORMAT_VERSIONS: &[FormatVersion] = &[
+    FormatVersion {
+        version: b'0',
+        blocks: &[
+            BLK_GYRO_SETUP,
+            BLK_TIME,
+            BLK_GYRO_DATA,
+            BLK_ACCEL_SETUP,
+            BLK_ACCEL_DATA,
+            BLK_TIME_OFFSET,
+            BLK_IMU_ORIENTATION,
+        ],
+    },
+    FormatVersion {
+        version: b'1',
+        blocks: &[
+            BLK_GYRO_SETUP,
+            BLK_TIME,
+            BLK_GYRO_DATA,
+            BLK_ACCEL_SETUP,
+            BLK_ACCEL_DATA,
+            BLK_TIME_OFFSET,
+            BLK_IMU_ORIENTATION,
+            BLK_KEYFRAME,
+            BLK_METADATA,
+        ],
+    },
+    FormatVersion {
+        version: b'2',
+        blocks: &[
+            BLK_GYRO_SETUP,
+            BLK_TIME,
+            BLK_GYRO_DATA,
+            BLK_ACCEL_SETUP,
+            BLK_ACCEL_DATA,
+            BLK_TIME_OFFSET,
+            BLK_IMU_ORIENTATION,
+            BLK_KEYFRAME,
+            BLK_METADATA,
+            BLK_HOST_TIME,
+        ],
+    },
+];
+
+pub const GYRO_REVISIONS: &[GyroRevision] = &[
+    GyroRevision {
+        revision: 0x01,
+        description: "closed-loop quantized angular acceleration, rANS with laplace models",
+    },
+    GyroRevision {
+        revision: 0x02,
+        description: "as 0x01 with 40-bit trigonometry in the rotation vector conversions",
+    },
+];
+
+// what the writer produces by default
+pub const LATEST_FORMAT_VERSION: u8 = b'2';
+pub const LATEST_GYRO_REVISION: u8 = 0x01;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Capabilities {
+    pub format_versions: &'static [FormatVersion],
+    pub gyro_revisions: &'static [GyroRevision],
+    pub latest_format_version: u8,
+    pub latest_gyro_revision: u8,
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        format_versions: FORMAT_VERSIONS,
+        gyro_revisions: GYRO_REVISIONS,
+        latest_format_version: LATEST_FORMAT_VERSION,
+        latest_gyro_revision: LATEST_GYRO_REVISION,
+    }
+}
+
+pub fn format_version(version: u8) -> Option<&'static FormatVersion> {
+    FORMAT_VERSIONS.iter().find(|x| x.version == version)
+}
+
+pub fn gyro_revision(revision: u8) -> Option<&'static GyroRevision> {
+    GYRO_REVISIONS.iter().find(|x| x.revision == revision)
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use ebin::{
+    esplog::{self, Error, Writer},
+    quat::{Fix, Quat},
+};
+
+// Writer::set_checked: input the quantizer would overflow on is rejected
+// without writing anything, the writer carries on as if it never came.
+
+fn quat(w: f32, x: f32, y: f32, z: f32) -> Quat {
+    Quat::new(
+        Fix::from_float(w),
+        Fix::from_float(x),
+        Fix::from_float(y),
+        Fix::from_float(z),
+    )
+}
+
+fn encode(qp: u8, checked: bool, bad: Option<&[Quat]>) -> Vec<u8> {
+    let mut w = Writer::new(qp).unwrap();
+    w.set_checked(checked);
+    w.set_keyframe_interval(2);
+    for (i, chunk) in common::input()[..400].chunks(100).enumerate() {
+        if let (1, Some(bad)) = (i, bad) {
+            assert!(w.gyro_data(bad).is_err());
+        }
+        w.gyro_data(chunk).unwrap();
+        w.time(100_000);
+    }
+    w.finish()
+}
+
+#[test]
+fn same_output_for_valid_input() {
+    for qp in [8, 14, 20] {
+        assert!(encode(qp, true, None) == encode(qp, false, None));
+    }
+}
+
+#[test]
+fn rejects_and_stays_usable() {
+    let big = quat(10.0, 10.0, -10.0, 3.0);
+    let mut w = Writer::new(14).unwrap();
+    w.set_checked(true);
+    assert_eq!(
+        w.gyro_data(&[big; 4]),
+        Err(Error::QuatOutOfRange { index: 0 })
+    );
+    let mut block = common::input()[..4].to_vec();
+    block[3] = quat(0.1, 0.0, 0.0, 0.1);
+    assert_eq!(w.gyro_data(&block), Err(Error::QuatOutOfRange { index: 3 }));
+
+    // a rejected block in the middle, with the setup block it would have
+    // been preceded by, leaves no trace
+    let mut long = common::input()[..50].to_vec();
+    long[40] = big;
+    for bad in [&[big; 4][..], &long] {
+        let buf = encode(14, true, Some(bad));
+        assert!(buf == encode(14, true, None));
+        assert_eq!(esplog::decode(&buf).unwrap().quats.len(), 400);
+    }
+}
+
+#[test]
+fn rejects_updates_too_large_for_qp() {
+    // at qp 31 any negative update is -16 rad, overflowing the angular
+    // velocity
+    let s = 0.5f32.sqrt();
+    let spin = [quat(1.0, 0.0, 0.0, 0.0), quat(s, -s, 0.0, 0.0)];
+    let still = [quat(1.0, 0.0, 0.0, 0.0); 8];
+    let mut w = Writer::new(31).unwrap();
+    w.set_checked(true);
+    w.set_keyframe_interval(1);
+    w.gyro_data(&still).unwrap();
+    assert!(matches!(
+        w.gyro_data(&spin),
+        Err(Error::QuatOutOfRange { index: 1 })
+    ));
+    w.gyro_data(&still).unwrap();
+
+    let mut expected = Writer::new(31).unwrap();
+    expected.set_keyframe_interval(1);
+    expected.gyro_data(&still).unwrap();
+    expected.gyro_data(&still).unwrap();
+    assert!(w.finish() == expected.finish());
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+// The ebin command line tool run on a cut of testdata/test.gcsv.
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ebin"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(o: &Output) -> String {
+    assert!(o.status.success(), "{}", String::from_utf8_lossy(&o.stderr));
+    String::from_utf8(o.stdout.clone()).unwrap()
+}
+
+fn stderr(o: &Output) -> String {
+    assert!(!o.status.success());
+    String::from_utf8(o.stderr.clone()).unwrap()
+}
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("ebin-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn path(&self, file: &str) -> String {
+        self.0.join(file).to_str().unwrap().to_owned()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn input(dir: &TempDir) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/test.gcsv");
+    let text = fs::read_to_string(path).unwrap();
+    let cut: Vec<&str> = text.lines().take(8 + 3000).collect();
+    let out = dir.path("in.gcsv");
+    fs::write(&out, cut.join("\n")).unwrap();
+    out
+}
+
+#[test]
+fn encode_decode_info_verify() {
+    let dir = TempDir::new("cli");
+    let gcsv = input(&dir);
+    let log = dir.path("out.esplog");
+
+    let out = stdout(&run(&[
+        "encode",
+        &gcsv,
+        &log,
+        "--qp",
+        "12",
+        "--keyframes",
+        "2",
+    ]));
+    assert!(out.starts_with("3000 samples, "), "{}", out);
+
+    let info = stdout(&run(&["info", &log, "--blocks"]));
+    for line in [
+        "gyro samples: 3000",
+        "accel samples: 3000",
+        "imu orientation: YxZ",
+        "gyro_scale: 0.00053263221",
+        "duration: 5.400 s",
+        "sample rate: 555.556 Hz",
+    ] {
+        assert!(info.contains(line), "{}\n{}", line, info);
+    }
+
+    let back = dir.path("back.gcsv");
+    stdout(&run(&["decode", &log, &back, "--threads", "2"]));
+    let text = fs::read_to_string(&back).unwrap();
+    assert!(text.starts_with("GYROFLOW IMU LOG\n"));
+    assert!(text.contains("\norientation,YxZ\n"));
+    assert_eq!(text.lines().count(), 3000 + 8);
+
+    let (csv, rates) = (dir.path("q.csv"), dir.path("r.csv"));
+    stdout(&run(&["decode", &log, &csv, "--rates", &rates]));
+    assert_eq!(fs::read_to_string(&csv).unwrap().lines().count(), 3001);
+    assert_eq!(fs::read_to_string(&rates).unwrap().lines().count(), 3001);
+
+    let verify = stdout(&run(&["verify", &gcsv, "--qp", "12"]));
+    assert!(verify.starts_with("3000 samples, "));
+    let rmse: f64 = verify
+        .lines()
+        .find_map(|l| l.strip_prefix("rmse: "))
+        .and_then(|l| l.trim_end_matches(" deg").parse().ok())
+        .unwrap();
+    assert!(rmse > 0.0 && rmse < 0.1, "{}", verify);
+}
+
+#[test]
+fn errors() {
+    let dir = TempDir::new("cli-errors");
+    let gcsv = input(&dir);
+    let log = dir.path("out.esplog");
+
+    assert!(stderr(&run(&[])).starts_with("usage:"));
+    assert!(stderr(&run(&["transcode"])).starts_with("usage:"));
+    assert!(stderr(&run(&["encode", &gcsv])).contains("missing argument"));
+    assert_eq!(
+        stderr(&run(&["encode", &gcsv, &log, "--qp", "40"])),
+        "error: qp 40 is out of range 0..=31\n"
+    );
+    assert_eq!(
+        stderr(&run(&["encode", &gcsv, &log, "--block", "0"])),
+        "error: invalid block size 0\n"
+    );
+    assert_eq!(
+        stderr(&run(&["encode", &gcsv, &log, "--qp"])),
+        "error: missing value for --qp\n"
+    );
+    assert_eq!(
+        stderr(&run(&["verify", &gcsv, "--qp", "x"])),
+        "error: invalid --qp x\n"
+    );
+
+    // not an EspLog file
+    assert!(stderr(&run(&["info", &gcsv])).starts_with("error: "));
+    assert!(stderr(&run(&["decode", &dir.path("missing"), &log])).starts_with("error: "));
+}
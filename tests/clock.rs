@@ -0,0 +1,79 @@
+use ebin::{
+    clock::ClockModel,
+    esplog::{self, Log, TimeMark, Writer},
+    metadata::{self, Metadata, Value},
+    quat::Quat,
+};
+
+// Clock models fitted to time blocks, host time blocks and the sample count.
+
+#[test]
+fn host_clock_through_container() {
+    // the host clock runs 50 ppm fast and starts 1 s later
+    let mut w = Writer::new(14).unwrap();
+    w.time_offset(2000);
+    w.host_time(1_002_000);
+    for i in 1..=20i64 {
+        w.time(100_000);
+        w.host_time(1_002_000 + i * 100_005);
+    }
+    let log = esplog::decode(&w.finish()).unwrap();
+    let clock = log.host_clock().unwrap();
+    assert!((clock.drift_ppm() - 50.0).abs() < 1e-6);
+    assert!(clock.residual_us < 1e-6);
+    assert_eq!(clock.map(2000), 1_002_000);
+    assert_eq!(clock.map(2_002_000), 3_002_100);
+}
+
+#[test]
+fn sample_clock_drift() {
+    // 1000 samples per time block of 1.0001 s at a nominal 1 kHz
+    let mut m = Metadata::new();
+    m.set(metadata::SAMPLE_RATE, Value::Float(1000.0));
+    let log = Log {
+        quats: vec![Quat::default(); 5000],
+        time: (1..=5)
+            .map(|i| TimeMark {
+                dt_us: 1_000_100,
+                quats: i * 1000,
+                accels: 0,
+            })
+            .collect(),
+        metadata: m,
+        ..Log::default()
+    };
+    assert!((log.sample_clock_drift_ppm().unwrap() - 100.0).abs() < 1e-6);
+
+    // resampled onto the host clock the rate follows the scale
+    let clock = ClockModel {
+        offset_us: 0.0,
+        scale: 1.0 + 100e-6,
+        residual_us: 0.0,
+    };
+    let t = log.timeline().unwrap();
+    let r = t.resampled(&clock);
+    assert_eq!(r.quats.us.len(), t.quats.us.len());
+    assert_eq!(r.quats.us[999], 1_000_200);
+    assert!((r.quats.sample_rate * clock.scale - t.quats.sample_rate).abs() < 1e-9);
+}
+
+#[test]
+fn not_enough_to_fit() {
+    assert_eq!(ClockModel::fit(&[]), None);
+    // all points at the same log time
+    assert_eq!(ClockModel::fit(&[(5.0, 1.0), (5.0, 2.0)]), None);
+    // a single point only gives an offset
+    let one = ClockModel::fit(&[(10.0, 25.0)]).unwrap();
+    assert_eq!((one.offset_us, one.scale), (15.0, 1.0));
+
+    let mut log = Log::default();
+    assert_eq!(log.host_clock(), None);
+    assert_eq!(log.sample_clock_drift_ppm(), None);
+    // time blocks alone are not enough without a nominal rate
+    log.time.push(TimeMark {
+        dt_us: 1000,
+        quats: 1,
+        accels: 0,
+    });
+    assert_eq!(log.sample_clock_drift_ppm(), None);
+}
@@ -0,0 +1,26 @@
+// Fixtures shared by the integration tests, included with `mod common;`.
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use ebin::{esplog::Writer, quat::Quat, rawquat};
+
+// the recorded stream the conformance vectors start from
+pub fn input() -> Vec<Quat> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/conformance/recorded.rawquat");
+    rawquat::read_file(path).unwrap()
+}
+
+// gyro, accel and time blocks of 100 samples with a keyframe before every
+// other gyro block
+pub fn encode(quats: &[Quat]) -> Vec<u8> {
+    let mut w = Writer::new(14).unwrap();
+    w.set_keyframe_interval(2);
+    for (i, chunk) in quats.chunks(100).enumerate() {
+        w.gyro_data(chunk).unwrap();
+        w.accel_data(&[[i as i16, -1, 2048]; 10]).unwrap();
+        w.time(100_000);
+    }
+    w.finish()
+}
@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ebin::{
+    compress::{compress_block, decompress_block},
+    esplog::{self, Writer},
+    quant::State,
+    quat::{Fix, Quat, RVec},
+    rawquat,
+};
+
+// Conformance vectors for encoder ports, see testdata/conformance/readme.txt.
+// The vectors are regenerated with
+//   cargo test --test conformance -- --ignored regenerate
+// which is only needed when the bitstream is changed on purpose.
+
+const BLOCK_SIZE: usize = 512;
+const SAMPLES: usize = 1024;
+const QPS: [u8; 4] = [8, 11, 14, 16];
+const CONTAINER_QP: u8 = 14;
+const STREAMS: [&str; 6] = ["still", "spin", "wobble", "noise", "fast", "recorded"];
+
+fn dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/conformance")
+}
+
+// integrates per-sample rotation vectors in rad
+fn integrate(mut rvec: impl FnMut(usize) -> [f64; 3]) -> Vec<Quat> {
+    let mut q = Quat::default();
+    (0..SAMPLES)
+        .map(|i| {
+            let [x, y, z] = rvec(i).map(|x| Fix::from_float(x as f32));
+            q = (q * Quat::from_rvec(&RVec::new(x, y, z))).normalize_safe();
+            q
+        })
+        .collect()
+}
+
+fn generate_input(name: &str) -> Vec<Quat> {
+    let dt = 1e-3;
+    match name {
+        "still" => vec![Quat::default(); SAMPLES],
+        "spin" => integrate(|_| [0.6 * dt, -0.3 * dt, 0.75 * dt]),
+        "wobble" => integrate(|i| {
+            let t = i as f64 * dt;
+            [
+                2.0 * (t * 7.0).sin() * dt,
+                1.5 * (t * 13.0 + 1.0).sin() * dt,
+                0.8 * (t * 3.0 + 2.0).cos() * dt,
+            ]
+        }),
+        "noise" => {
+            let mut seed = 0x2545_f491u32;
+            integrate(move |_| {
+                [0; 3].map(|_| {
+                    seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    ((seed >> 8) as f64 / (1u32 << 24) as f64 - 0.5) * 4.0 * dt
+                })
+            })
+        }
+        // saturates the quantizer
+        "fast" => integrate(|i| {
+            let t = i as f64 * dt;
+            [
+                25.0 * (t * 40.0).sin() * dt,
+                -18.0 * dt,
+                30.0 * (t * 25.0).cos() * dt,
+            ]
+        }),
+        "recorded" => {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/test.rawquat");
+            rawquat::read_file(path).unwrap()[..SAMPLES].to_vec()
+        }
+        _ => unreachable!(),
+    }
+}
+
+// raw block stream as written by the firmware: compressed blocks of
+// BLOCK_SIZE back to back, plus the model index of every block
+fn encode(quats: &[Quat], qp: u8) -> (Vec<u8>, Vec<u8>) {
+    let mut state = State::new();
+    let mut scratch = vec![0; BLOCK_SIZE * 64];
+    // rANS never spends more than two bytes on a symbol
+    let mut out = vec![0; 6 + 2 * scratch.len()];
+    let mut data = vec![];
+    let mut models = vec![];
+    for chunk in quats.chunks(BLOCK_SIZE) {
+        let res = compress_block(&state, chunk, qp, &mut out, &mut scratch).unwrap();
+        models.push(out[1] & 0x1f);
+        data.extend_from_slice(&out[..res.bytes_put]);
+        state = res.new_state;
+    }
+    (data, models)
+}
+
+fn decode(data: &[u8], samples: usize) -> Vec<Quat> {
+    let mut state = State::new();
+    let mut quats = vec![Quat::default(); samples];
+    let mut pos = 0;
+    for chunk in quats.chunks_mut(BLOCK_SIZE) {
+        let res = decompress_block(&state, &data[pos..], chunk).unwrap();
+        assert_eq!(res.quats_put, chunk.len());
+        state = res.new_state;
+        pos += res.bytes_eaten;
+    }
+    assert_eq!(pos, data.len(), "trailing bytes");
+    quats
+}
+
+fn encode_container(quats: &[Quat]) -> Vec<u8> {
+    let mut w = Writer::new(CONTAINER_QP).unwrap();
+    w.set_keyframe_interval(1);
+    w.time_offset(-250);
+    for chunk in quats.chunks(BLOCK_SIZE) {
+        w.gyro_data(chunk).unwrap();
+        w.time((chunk.len() * 1000) as u32);
+    }
+    w.finish()
+}
+
+fn read(name: &str) -> Vec<u8> {
+    let path = dir().join(name);
+    fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+fn read_quats(name: &str) -> Vec<Quat> {
+    rawquat::decode(&read(name)).unwrap()
+}
+
+fn manifest_line(name: &str, qp: u8, bytes: usize, models: &[u8]) -> String {
+    let models: Vec<String> = models.iter().map(|m| m.to_string()).collect();
+    format!(
+        "{} {} {} {} {} {}\n",
+        name,
+        qp,
+        BLOCK_SIZE,
+        SAMPLES,
+        bytes,
+        models.join(",")
+    )
+}
+
+#[test]
+#[ignore]
+fn regenerate() {
+    fs::create_dir_all(dir()).unwrap();
+    let mut manifest = String::from("# stream qp block_size samples bytes models\n");
+    for name in STREAMS {
+        let input = generate_input(name);
+        rawquat::write_file(dir().join(format!("{}.rawquat", name)), &input, false).unwrap();
+        for qp in QPS {
+            let (data, models) = encode(&input, qp);
+            let decoded = decode(&data, input.len());
+            fs::write(dir().join(format!("{}_qp{}.bin", name, qp)), &data).unwrap();
+            let path = dir().join(format!("{}_qp{}.rawquat", name, qp));
+            rawquat::write_file(path, &decoded, false).unwrap();
+            manifest += &manifest_line(name, qp, data.len(), &models);
+        }
+        fs::write(
+            dir().join(format!("{}.esplog", name)),
+            encode_container(&input),
+        )
+        .unwrap();
+    }
+    fs::write(dir().join("manifest.txt"), manifest).unwrap();
+}
+
+#[test]
+fn encoder_matches_vectors() {
+    let mut manifest = String::from("# stream qp block_size samples bytes models\n");
+    for name in STREAMS {
+        let input = read_quats(&format!("{}.rawquat", name));
+        assert_eq!(input.len(), SAMPLES, "{}", name);
+        for qp in QPS {
+            let (data, models) = encode(&input, qp);
+            let expected = read(&format!("{}_qp{}.bin", name, qp));
+            assert!(
+                data == expected,
+                "{} qp {}: compressed bytes differ",
+                name,
+                qp
+            );
+            manifest += &manifest_line(name, qp, data.len(), &models);
+        }
+    }
+    let expected = String::from_utf8(read("manifest.txt")).unwrap();
+    assert_eq!(manifest, expected);
+}
+
+#[test]
+fn decoder_matches_vectors() {
+    for name in STREAMS {
+        for qp in QPS {
+            let data = read(&format!("{}_qp{}.bin", name, qp));
+            let expected = read_quats(&format!("{}_qp{}.rawquat", name, qp));
+            assert!(
+                decode(&data, SAMPLES) == expected,
+                "{} qp {}: decoded quats differ",
+                name,
+                qp
+            );
+        }
+    }
+}
+
+#[test]
+fn container_matches_vectors() {
+    for name in STREAMS {
+        let input = read_quats(&format!("{}.rawquat", name));
+        let expected = read(&format!("{}.esplog", name));
+        assert!(
+            encode_container(&input) == expected,
+            "{}: container bytes differ",
+            name
+        );
+
+        let log = esplog::decode(&expected).unwrap();
+        let decoded = read_quats(&format!("{}_qp{}.rawquat", name, CONTAINER_QP));
+        assert!(log.quats == decoded, "{}: container decode differs", name);
+        assert_eq!(log.time_offset_us, Some(-250));
+    }
+}
+
+// generated streams must still produce the checked-in inputs, otherwise a
+// regeneration would silently change every vector
+#[test]
+fn inputs_are_reproducible() {
+    for name in STREAMS {
+        let input = read_quats(&format!("{}.rawquat", name));
+        assert!(generate_input(name) == input, "{}: input differs", name);
+    }
+}
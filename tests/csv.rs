@@ -0,0 +1,87 @@
+mod common;
+
+use std::path::Path;
+
+use ebin::{
+    csv,
+    esplog::{self, Log, Writer},
+    gcsv::{Error, Gcsv},
+};
+
+// Quaternion and rate CSV exports of a decoded log.
+
+fn log() -> Log {
+    let quats = common::input();
+    let mut w = Writer::new(14).unwrap();
+    for chunk in quats[..200].chunks(100) {
+        w.gyro_data(chunk).unwrap();
+        w.time(200_000);
+    }
+    esplog::decode(&w.finish()).unwrap()
+}
+
+fn rows(text: &[u8]) -> Vec<Vec<f64>> {
+    std::str::from_utf8(text)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|l| l.split(',').map(|x| x.parse().unwrap()).collect())
+        .collect()
+}
+
+#[test]
+fn quats_and_rates() {
+    let log = log();
+    let mut text = vec![];
+    csv::write_quats(&mut text, &log).unwrap();
+    assert!(text.starts_with(b"t,w,x,y,z\n"));
+    let quats = rows(&text);
+    assert_eq!(quats.len(), 200);
+    assert_eq!(quats[0][0], 0.002);
+    assert_eq!(quats[199][0], 0.4);
+    for (row, q) in quats.iter().zip(&log.quats) {
+        let q = [q.w, q.x, q.y, q.z].map(|x| x.to_float() as f64);
+        for (a, b) in row[1..].iter().zip(q) {
+            assert!((a - b).abs() < 1e-8);
+        }
+    }
+
+    let mut text = vec![];
+    csv::write_rates(&mut text, &log).unwrap();
+    assert!(text.starts_with(b"t,gx,gy,gz\n"));
+    let gcsv = Gcsv::from_log(&log).unwrap();
+    for (row, s) in rows(&text).iter().zip(&gcsv.samples) {
+        assert!((row[0] - s.t).abs() < 1e-6);
+        for (a, b) in row[1..].iter().zip(s.gyro) {
+            assert!((a - b).abs() < 1e-8);
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("ebin-csv-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (q, r) = (dir.join("q.csv"), dir.join("r.csv"));
+    csv::write_files(&log, &q, Some(&r)).unwrap();
+    assert_eq!(rows(&std::fs::read(&q).unwrap()), quats);
+    assert!(r.exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let mut log = log();
+    log.time.clear();
+    assert!(matches!(
+        csv::write_quats(vec![], &log),
+        Err(Error::NoTimestamps)
+    ));
+    assert!(matches!(
+        csv::write_rates(vec![], &log),
+        Err(Error::NoTimestamps)
+    ));
+
+    let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("no/such/dir/q.csv");
+    assert!(matches!(
+        csv::write_files(&self::log(), &missing, None),
+        Err(Error::Io(_))
+    ));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{encode, input};
+use ebin::{
+    compress::compress_block,
+    esplog::{self, DecodeOptions, Error, Writer, BLK_GYRO_DATA, BLK_KEYFRAME},
+    quant::{State, MAX_QP},
+};
+
+// EspLog container decoding of well-formed, truncated and corrupted files.
+
+fn gyro_data_pos(buf: &[u8]) -> usize {
+    esplog::scan(buf)
+        .unwrap()
+        .iter()
+        .find(|b| b.id == BLK_GYRO_DATA)
+        .unwrap()
+        .pos
+}
+
+#[test]
+fn parallel_matches_sequential() {
+    let quats = input();
+    let buf = encode(&quats);
+    let keyframes = esplog::scan(&buf)
+        .unwrap()
+        .iter()
+        .filter(|b| b.id == BLK_KEYFRAME)
+        .count();
+    assert_eq!(keyframes, 6);
+
+    let seq = esplog::decode(&buf).unwrap();
+    assert_eq!(seq.quats.len(), quats.len());
+    for threads in [0, 1, 2, 3, 6, 16] {
+        let par = esplog::decode_parallel(&buf, threads).unwrap();
+        assert!(par == seq, "{} threads", threads);
+    }
+    let opts = DecodeOptions {
+        threads: 4,
+        camera_frame: false,
+    };
+    assert!(esplog::decode_with(&buf, &opts).unwrap() == seq);
+}
+
+#[test]
+fn parallel_rejects_bad_tail() {
+    let buf = encode(&input());
+    let last = esplog::scan(&buf)
+        .unwrap()
+        .iter()
+        .rfind(|b| b.id == BLK_KEYFRAME)
+        .unwrap()
+        .pos;
+
+    // the last segment is cut short
+    let cut = &buf[..buf.len() - 3];
+    assert_eq!(
+        esplog::decode_parallel(cut, 4),
+        Err(Error::Truncated { pos: last })
+    );
+    assert!(esplog::decode(cut).is_err());
+
+    // garbage after the last segment where a keyframe should be
+    let mut tail = buf.clone();
+    tail.extend_from_slice(&[BLK_GYRO_DATA, 1, 2]);
+    assert_eq!(
+        esplog::decode_parallel(&tail, 4),
+        Err(Error::CorruptBlock { pos: buf.len() })
+    );
+
+    // a segment length pointing past the end
+    let mut long = buf.clone();
+    long[last + 1..last + 5].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        esplog::decode_parallel(&long, 4),
+        Err(Error::Truncated { pos: last })
+    );
+}
+
+#[test]
+fn rejects_bad_qp_and_model() {
+    let buf = encode(&input());
+    let pos = gyro_data_pos(&buf);
+
+    let mut bad_qp = buf.clone();
+    bad_qp[pos + 1] = 40;
+    assert_eq!(esplog::decode(&bad_qp), Err(Error::CorruptBlock { pos }));
+
+    let mut bad_model = buf.clone();
+    bad_model[pos + 2] = (bad_model[pos + 2] & 0xe0) | 20;
+    assert_eq!(esplog::decode(&bad_model), Err(Error::CorruptBlock { pos }));
+    assert_eq!(esplog::scan(&bad_model), Err(Error::CorruptBlock { pos }));
+}
+
+#[test]
+fn writer_validates_qp() {
+    assert!(Writer::new(MAX_QP).is_ok());
+    assert_eq!(
+        Writer::new(MAX_QP + 1).err(),
+        Some(Error::InvalidQp { qp: MAX_QP + 1 })
+    );
+    // the block level API refuses it as well
+    let quats = input();
+    let (mut data, mut scratch) = (vec![0; 4096], vec![0; 4096]);
+    let state = State::new();
+    assert!(compress_block(&state, &quats[..100], 32, &mut data, &mut scratch).is_none());
+    assert!(compress_block(&state, &quats[..100], 14, &mut data, &mut scratch).is_some());
+}
+
+// whatever the bytes, decoding fails with an error instead of panicking
+#[test]
+fn corrupt_input_does_not_panic() {
+    let buf = encode(&input());
+    let mut seed = 0x2545_f491u32;
+    let mut next = move || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 8) as usize
+    };
+    for _ in 0..1000 {
+        let mut bad = buf.clone();
+        for _ in 0..1 + next() % 4 {
+            let i = esplog::HEADER_SIZE + next() % (bad.len() - esplog::HEADER_SIZE);
+            bad[i] = next() as u8;
+        }
+        bad.truncate(esplog::HEADER_SIZE + next() % (bad.len() - esplog::HEADER_SIZE + 1));
+        let _ = esplog::decode(&bad);
+        let _ = esplog::scan(&bad);
+        let _ = esplog::decode_parallel(&bad, 3);
+    }
+}
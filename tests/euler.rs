@@ -0,0 +1,202 @@
+use std::f64::consts::FRAC_PI_2;
+
+use ebin::{
+    fix32::Fix32,
+    quat::{EulerOrder, Fix, Quat, Vec3},
+    scalar::Scalar,
+};
+
+// Euler angle and rotation matrix conversions against rotations built in f64
+// and applied to points. Matrix entries add up the errors of the fixed point
+// sin and cos.
+
+const FIX_TOL: f64 = 1e-2;
+const F64_TOL: f64 = 1e-9;
+
+const ORDERS: [EulerOrder; 6] = [
+    EulerOrder::XYZ,
+    EulerOrder::XZY,
+    EulerOrder::YXZ,
+    EulerOrder::YZX,
+    EulerOrder::ZXY,
+    EulerOrder::ZYX,
+];
+
+fn axes(order: EulerOrder) -> [usize; 3] {
+    match order {
+        EulerOrder::XYZ => [0, 1, 2],
+        EulerOrder::XZY => [0, 2, 1],
+        EulerOrder::YXZ => [1, 0, 2],
+        EulerOrder::YZX => [1, 2, 0],
+        EulerOrder::ZXY => [2, 0, 1],
+        EulerOrder::ZYX => [2, 1, 0],
+    }
+}
+
+type M = [[f64; 3]; 3];
+
+fn axis_matrix(axis: usize, a: f64) -> M {
+    let (s, c) = a.sin_cos();
+    match axis {
+        0 => [[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]],
+        1 => [[c, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, c]],
+        _ => [[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]],
+    }
+}
+
+fn mat_mul(a: M, b: M) -> M {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+// intrinsic rotations multiply left to right
+fn euler_matrix(order: EulerOrder, angles: [f64; 3]) -> M {
+    let [i, j, k] = axes(order);
+    mat_mul(
+        mat_mul(axis_matrix(i, angles[0]), axis_matrix(j, angles[1])),
+        axis_matrix(k, angles[2]),
+    )
+}
+
+fn mat_diff(a: M, b: M) -> f64 {
+    (0..9)
+        .map(|n| (a[n / 3][n % 3] - b[n / 3][n % 3]).abs())
+        .fold(0.0, f64::max)
+}
+
+fn to_f64<T: Scalar>(m: [[T; 3]; 3]) -> M {
+    m.map(|row| row.map(|x| x.to_f64()))
+}
+
+fn angles() -> Vec<[f64; 3]> {
+    let mut seed = 0x2545_f491u32;
+    let mut next = move || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 8) as f64 / (1u32 << 24) as f64 * 2.0 - 1.0
+    };
+    let pi = std::f64::consts::PI;
+    let mut v: Vec<[f64; 3]> = (0..200)
+        .map(|_| [next() * pi, next() * FRAC_PI_2 * 0.99, next() * pi])
+        .collect();
+    v.push([0.0, 0.0, 0.0]);
+    v.push([0.3, 0.0, -2.0]);
+    v.push([-3.0, 1.5, 3.0]);
+    v
+}
+
+fn check<T: Scalar>(tol: f64) {
+    for order in ORDERS {
+        for a in angles() {
+            let expected = euler_matrix(order, a);
+            let q = Quat::from_euler(order, a.map(T::from_f64));
+            let m = to_f64(q.to_matrix());
+            assert!(mat_diff(m, expected) < tol, "{:?} {:?}", order, a);
+
+            // away from gimbal lock the angles come back, the outer ones
+            // less precisely the closer the middle angle gets to it
+            let back = q.to_euler(order).map(|x| x.to_f64());
+            let outer_tol = tol / a[1].cos();
+            for (n, (got, want)) in back.iter().zip(a).enumerate() {
+                let t = if n == 1 { tol } else { outer_tol };
+                assert!((got - want).abs() < t, "{:?} {:?} {:?}", order, a, back);
+            }
+
+            // the matrix agrees with rotate_point and converts back
+            let p = Vec3::new(0.3, -0.7, 0.2);
+            let r = q.rotate_point(&p.map(T::from_f64)).map(|x| x.to_f64());
+            let e = (0..3)
+                .map(|i| expected[i][0] * p.x + expected[i][1] * p.y + expected[i][2] * p.z)
+                .collect::<Vec<_>>();
+            assert!(
+                (r.x - e[0]).abs() < tol && (r.y - e[1]).abs() < tol && (r.z - e[2]).abs() < tol
+            );
+            let qm = Quat::from_matrix(&q.to_matrix());
+            assert!(mat_diff(to_f64(qm.to_matrix()), expected) < tol);
+        }
+    }
+}
+
+#[test]
+fn fixed_point() {
+    check::<Fix>(FIX_TOL);
+}
+
+#[test]
+fn float() {
+    check::<f64>(F64_TOL);
+}
+
+#[test]
+fn gimbal_lock() {
+    for order in ORDERS {
+        for middle in [FRAC_PI_2, -FRAC_PI_2] {
+            let a = [0.7, middle, -0.4];
+            let expected = euler_matrix(order, a);
+            let q = Quat::from_euler(order, a);
+            let back = q.to_euler(order);
+            // the third angle is folded into the first
+            assert_eq!(back[2], 0.0);
+            assert!((back[1] - middle).abs() < 1e-6, "{:?} {:?}", order, back);
+            assert!(mat_diff(euler_matrix(order, back), expected) < 1e-9);
+
+            // the fixed point outer angles are noise here but still
+            // describe the rotation
+            let qf = Quat::from_euler(order, a.map(Fix::from_f64));
+            let back = qf.to_euler(order).map(|x| x.to_f64());
+            assert!(mat_diff(euler_matrix(order, back), expected) < FIX_TOL);
+
+            // just outside the lock all three angles are returned, the
+            // threshold scales with the precision of the type
+            for offset in [1e-5, 1e-9] {
+                let a = [0.7, middle * (1.0 - offset), -0.4];
+                let back = Quat::from_euler(order, a).to_euler(order);
+                assert!(back[2] != 0.0);
+                assert!(mat_diff(euler_matrix(order, back), euler_matrix(order, a)) < 1e-9);
+            }
+        }
+    }
+}
+
+#[test]
+fn gimbal_lock_threshold() {
+    // 128 EPSILON, also where 128.0 itself does not fit
+    assert_eq!(Quat::<Fix>::gimbal_lock_threshold().to_raw(), 128);
+    assert_eq!(Quat::<Fix32<30>>::gimbal_lock_threshold().to_raw(), 128);
+    assert_eq!(Quat::<f64>::gimbal_lock_threshold(), 128.0 * f64::EPSILON);
+
+    // The Q27 cos of the middle angle comes from squares, entries of a few
+    // raw units give 0 and the lock branch
+    for r in 94_906_261..=94_906_271 {
+        let (r, zero) = (Fix::from_raw(r), Fix::from_raw(0));
+        let back = Quat::new(r, zero, r, zero).to_euler(EulerOrder::XYZ);
+        assert_eq!(back.map(|x| x.to_raw()), [0, 210_828_714, 0]);
+    }
+    // 2e-4 rad away the cos is far above the threshold
+    let a = [0.3, FRAC_PI_2 - 2e-4, 0.2].map(Fix::from_f64);
+    let back = Quat::from_euler(EulerOrder::XYZ, a).to_euler(EulerOrder::XYZ);
+    assert!(back[2] != Fix::from_raw(0));
+}
+
+#[test]
+fn matrix_branches() {
+    // rotations by pi about each axis take the non-trace branches
+    for q in [
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [0.1, 0.7, -0.7, 0.1],
+        [1.0, 0.0, 0.0, 0.0],
+    ] {
+        let q = Quat::<f64>::new(q[0], q[1], q[2], q[3]).normalize_safe();
+        let back = Quat::from_matrix(&q.to_matrix());
+        let s = if back.dot(&q) < 0.0 { -1.0 } else { 1.0 };
+        for (a, b) in [(back.w, q.w), (back.x, q.x), (back.y, q.y), (back.z, q.z)] {
+            assert!((a - s * b).abs() < 1e-12, "{:?} {:?}", q, back);
+        }
+    }
+}
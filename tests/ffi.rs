@@ -0,0 +1,132 @@
+mod common;
+
+use std::ptr;
+
+use ebin::{esplog, ffi::*};
+
+// The C interface called the way firmware would, through raw pointers.
+
+// the shared input as the raw Q27 values firmware passes
+fn input() -> Vec<i32> {
+    common::input()[..500]
+        .iter()
+        .flat_map(|q| [q.w, q.x, q.y, q.z].map(|x| x.to_raw()))
+        .collect()
+}
+
+// drains the encoder through a small buffer, as a logger writing to flash
+unsafe fn read_all(enc: *mut EbinEncoder, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 37];
+    loop {
+        let n = ebin_encoder_read(enc, buf.as_mut_ptr(), buf.len());
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+}
+
+#[test]
+fn encode_and_decode() {
+    let quats = input();
+    let mut file = vec![];
+    unsafe {
+        let enc = ebin_encoder_new(14);
+        assert!(!enc.is_null());
+        assert_eq!(ebin_encoder_set_keyframe_interval(enc, 2), EBIN_OK);
+        assert_eq!(ebin_encoder_imu_orientation(enc, b"YxZ".as_ptr()), EBIN_OK);
+        assert_eq!(ebin_encoder_time_offset(enc, 100), EBIN_OK);
+        for chunk in quats.chunks(4 * 100) {
+            let n = chunk.len() / 4;
+            assert_eq!(ebin_encoder_gyro_data(enc, chunk.as_ptr(), n), EBIN_OK);
+            let accels = vec![7i16; 3 * n];
+            assert_eq!(ebin_encoder_accel_data(enc, accels.as_ptr(), n), EBIN_OK);
+            assert_eq!(ebin_encoder_time(enc, 100_000), EBIN_OK);
+            read_all(enc, &mut file);
+        }
+        assert_eq!(ebin_encoder_finish(enc), EBIN_OK);
+        read_all(enc, &mut file);
+        ebin_encoder_free(enc);
+    }
+    let log = esplog::decode(&file).unwrap();
+    assert_eq!(log.quats.len(), 500);
+    assert_eq!(log.imu_orientation, Some(*b"YxZ"));
+
+    unsafe {
+        let mut log = ptr::null_mut();
+        assert_eq!(ebin_decode(file.as_ptr(), file.len(), &mut log), EBIN_OK);
+        assert_eq!(ebin_log_quat_count(log), 500);
+        assert_eq!(ebin_log_accel_count(log), 500);
+
+        let mut out = vec![0i32; 4 * 500];
+        assert_eq!(ebin_log_quats(log, out.as_mut_ptr(), 500), 500);
+        for (a, b) in out.iter().zip(&quats) {
+            assert!((a - b).abs() < 1 << 14);
+        }
+        let mut f = vec![0f32; 4 * 2];
+        assert_eq!(ebin_log_quats_f32(log, f.as_mut_ptr(), 2), 2);
+        assert_eq!(f[0], (out[0] as f64 / (1 << 27) as f64) as f32);
+        let mut accels = vec![0i16; 3 * 600];
+        assert_eq!(ebin_log_accels(log, accels.as_mut_ptr(), 600), 500);
+        assert_eq!(accels[..3], [7, 7, 7]);
+        let mut times = vec![0i64; 500];
+        assert_eq!(ebin_log_quat_times(log, times.as_mut_ptr(), 500), 500);
+        assert_eq!((times[0], times[499]), (1100, 500_100));
+        ebin_log_free(log);
+    }
+}
+
+#[test]
+fn errors() {
+    unsafe {
+        assert!(ebin_encoder_new(32).is_null());
+        assert_eq!(ebin_encoder_time(ptr::null_mut(), 1), EBIN_ERR_NULL);
+        assert_eq!(ebin_encoder_finish(ptr::null_mut()), EBIN_ERR_NULL);
+        assert_eq!(ebin_encoder_pending(ptr::null_mut()), 0);
+        ebin_encoder_free(ptr::null_mut());
+        ebin_log_free(ptr::null_mut());
+        assert_eq!(ebin_log_quat_count(ptr::null()), 0);
+
+        let enc = ebin_encoder_new(14);
+        assert_eq!(ebin_encoder_gyro_data(enc, ptr::null(), 1), EBIN_ERR_NULL);
+        assert_eq!(ebin_encoder_gyro_data(enc, ptr::null(), 0), EBIN_OK);
+        assert_eq!(
+            ebin_encoder_imu_orientation(enc, b"XXZ".as_ptr()),
+            EBIN_ERR_INVALID_ORIENTATION
+        );
+        assert_eq!(
+            ebin_encoder_set_gyro_revision(enc, 9),
+            EBIN_ERR_UNSUPPORTED_REVISION
+        );
+        // out of range input is an error only in checked mode
+        let big = [10 << 27, 10 << 27, -10 << 27, 3 << 27];
+        assert_eq!(ebin_encoder_set_checked(enc, 1), EBIN_OK);
+        assert_eq!(
+            ebin_encoder_gyro_data(enc, big.as_ptr(), 1),
+            EBIN_ERR_QUAT_OUT_OF_RANGE
+        );
+        let quats = input();
+        assert_eq!(ebin_encoder_gyro_data(enc, quats.as_ptr(), 10), EBIN_OK);
+        assert_eq!(ebin_encoder_finish(enc), EBIN_OK);
+        assert_eq!(ebin_encoder_finish(enc), EBIN_ERR_FINISHED);
+        assert_eq!(ebin_encoder_keyframe(enc), EBIN_ERR_FINISHED);
+        let mut out = vec![];
+        read_all(enc, &mut out);
+        assert_eq!(&out[..6], b"EspLog");
+        ebin_encoder_free(enc);
+
+        let mut log = ptr::null_mut();
+        assert_eq!(
+            ebin_decode(b"EspLug2".as_ptr(), 7, &mut log),
+            EBIN_ERR_BAD_MAGIC
+        );
+        assert!(log.is_null());
+        // a time block cut short
+        out.extend_from_slice(&[esplog::BLK_TIME, 1, 2]);
+        assert_eq!(
+            ebin_decode(out.as_ptr(), out.len(), &mut log),
+            EBIN_ERR_TRUNCATED
+        );
+        assert_eq!(ebin_decode(ptr::null(), 0, &mut log), EBIN_ERR_NULL);
+    }
+}
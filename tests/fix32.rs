@@ -0,0 +1,93 @@
+use ebin::{
+    fix32::Fix32,
+    quat::{Fix, Quat},
+};
+
+fn f(x: f32) -> Fix {
+    Fix::from_float(x)
+}
+
+#[test]
+fn checked_matches_operators_in_range() {
+    let vals = [-7.5, -1.0, -0.3, 0.0, 0.001, 0.7, 2.0, 5.25];
+    for &a in &vals {
+        for &b in &vals {
+            let (a, b) = (f(a), f(b));
+            assert_eq!(a.checked_add(b), Some(a + b));
+            assert_eq!(a.checked_sub(b), Some(a - b));
+            if (a.to_float() * b.to_float()).abs() < 15.0 {
+                assert_eq!(a.checked_mul(b), Some(a * b));
+                assert_eq!(a.saturating_mul(b), a * b);
+                assert_eq!(a.wrapping_mul(b), a * b);
+            }
+            if b.to_raw() != 0 && (a.to_float() / b.to_float()).abs() < 15.0 {
+                assert_eq!(a.checked_div(b), Some(a / b));
+                assert_eq!(a.saturating_div(b), a / b);
+                assert_eq!(a.wrapping_div(b), a / b);
+            }
+        }
+    }
+}
+
+#[test]
+fn checked_detects_overflow() {
+    let eps = Fix::from_raw(1);
+    assert_eq!(Fix::MAX.checked_add(eps), None);
+    assert_eq!(Fix::MIN.checked_sub(eps), None);
+    assert_eq!(Fix::MIN.checked_neg(), None);
+    assert_eq!(f(8.0).checked_mul(f(2.0)), None);
+    assert_eq!(f(-8.0).checked_mul(f(2.5)), None);
+    assert_eq!(f(-8.0).checked_mul(f(2.0)), Some(Fix::MIN));
+    assert_eq!(f(8.0).checked_div(f(0.25)), None);
+    assert_eq!(f(1.0).checked_div(Fix::ZERO), None);
+    assert_eq!(f(4.0).checked_mul(f(-2.0)), Some(f(-8.0)));
+}
+
+#[test]
+fn saturating_clamps() {
+    let eps = Fix::from_raw(1);
+    assert_eq!(Fix::MAX.saturating_add(eps), Fix::MAX);
+    assert_eq!(Fix::MIN.saturating_sub(eps), Fix::MIN);
+    assert_eq!(Fix::MIN.saturating_neg(), Fix::MAX);
+    assert_eq!(f(8.0).saturating_mul(f(2.0)), Fix::MAX);
+    assert_eq!(f(8.0).saturating_mul(f(-2.0)), Fix::MIN);
+    assert_eq!(f(-8.0).saturating_div(f(0.25)), Fix::MIN);
+    assert_eq!(f(1.0).saturating_div(Fix::ZERO), Fix::MAX);
+    assert_eq!(f(-1.0).saturating_div(Fix::ZERO), Fix::MIN);
+    assert_eq!(Fix::ZERO.saturating_div(Fix::ZERO), Fix::ZERO);
+}
+
+#[test]
+fn wrapping_wraps() {
+    let eps = Fix::from_raw(1);
+    assert_eq!(Fix::MAX.wrapping_add(eps), Fix::MIN);
+    assert_eq!(Fix::MIN.wrapping_sub(eps), Fix::MAX);
+    assert_eq!(Fix::MIN.wrapping_neg(), Fix::MIN);
+    // 8 * 2 = 16 is 2^31 in Q27
+    assert_eq!(f(8.0).wrapping_mul(f(2.0)), Fix::MIN);
+    // other Q formats round the same way
+    let a = Fix32::<16>::from_raw(3);
+    let half = Fix32::<16>::from_float(0.5);
+    assert_eq!(a.wrapping_mul(half).to_raw(), 2);
+    assert_eq!((-a).wrapping_mul(half).to_raw(), -2);
+}
+
+#[test]
+fn quat_checked_ops() {
+    let a = Quat::new(f(0.8), f(0.36), f(-0.48), f(0.0));
+    let b = Quat::new(f(0.5), f(-0.5), f(0.5), f(0.5));
+    assert_eq!(a.checked_mul(&b), Some(a * b));
+    assert_eq!(a.checked_add(&b), Some(a + b));
+    assert_eq!(a.checked_smul(f(2.0)), Some(a.smul(f(2.0))));
+    assert_eq!(a.checked_sdiv(f(2.0)), Some(a.sdiv(f(2.0))));
+    assert_eq!(a.checked_norm(), Some(a.norm()));
+    assert_eq!(b.checked_normalize(), Some(b.clone().normalize()));
+
+    let big = Quat::new(f(10.0), f(10.0), f(0.0), f(0.0));
+    assert_eq!(big.checked_mul(&big), None);
+    assert_eq!(big.checked_add(&big), None);
+    assert_eq!(big.checked_norm(), None);
+    assert_eq!(a.checked_sdiv(Fix::ZERO), None);
+    let zero = Quat::new(Fix::ZERO, Fix::ZERO, Fix::ZERO, Fix::ZERO);
+    assert_eq!(zero.checked_normalize(), None);
+}
@@ -0,0 +1,148 @@
+use ebin::{
+    fix64::Fix64,
+    quat::{Fix, Quat, Vec3},
+};
+
+// Fix64 arithmetic and functions against f64. Inputs stay below 2^53 raw so
+// they convert to f64 exactly.
+
+type F = Fix64<48>;
+
+const ULP: f64 = 1.0 / (1u64 << 48) as f64;
+const BOUND: f64 = 0.6;
+
+fn inputs() -> Vec<F> {
+    let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+    let mut v: Vec<F> = (0..200_000)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            // spread over magnitudes from 2^-48 to 2^5
+            let bits = seed % 54;
+            let raw = (seed >> 10) as i64 & ((1i64 << bits) - 1);
+            F::from_raw(if seed & 1 == 0 { raw } else { -raw })
+        })
+        .collect();
+    v.extend([F::ZERO, F::PI, -F::PI, F::HALF_PI, F::from_i32(1)]);
+    v
+}
+
+fn f(x: F) -> f64 {
+    x.to_raw() as f64 * ULP
+}
+
+fn check(name: &str, samples: impl Iterator<Item = (F, f64)>) {
+    let worst = samples
+        .map(|(got, exact)| (f(got) - exact).abs() / ULP)
+        .fold(0.0, f64::max);
+    assert!(worst <= BOUND, "{}: error {} ulp", name, worst);
+}
+
+#[test]
+fn sin_cos() {
+    let xs = inputs();
+    check("sin", xs.iter().map(|&x| (x.sin(), f(x).sin())));
+    check("cos", xs.iter().map(|&x| (x.cos(), f(x).cos())));
+}
+
+#[test]
+fn atan2() {
+    let xs = inputs();
+    check(
+        "atan2",
+        xs.iter()
+            .zip(xs.iter().rev())
+            .map(|(&y, &x)| (y.atan2(x), f(y).atan2(f(x)))),
+    );
+}
+
+#[test]
+fn sqrt() {
+    let xs: Vec<F> = inputs().into_iter().filter(|x| *x >= F::ZERO).collect();
+    check("sqrt", xs.iter().map(|&x| (x.sqrt(), f(x).sqrt())));
+}
+
+#[test]
+fn full_range_q27() {
+    // sin and cos of Fix64<27> cover the range of Fix32<27> and beyond
+    let ulp = 1.0 / (1u64 << 27) as f64;
+    for raw in (-(1i64 << 40)..(1i64 << 40)).step_by(1_000_003_001) {
+        let x = Fix64::<27>::from_raw(raw);
+        let xf = raw as f64 * ulp;
+        assert!(
+            (x.sin().to_float() - xf.sin()).abs() <= BOUND * ulp,
+            "{}",
+            xf
+        );
+        assert!(
+            (x.cos().to_float() - xf.cos()).abs() <= BOUND * ulp,
+            "{}",
+            xf
+        );
+    }
+}
+
+#[test]
+fn checked_saturating_wrapping() {
+    let eps = F::from_raw(1);
+    assert_eq!(F::MAX.checked_add(eps), None);
+    assert_eq!(F::MIN.checked_neg(), None);
+    assert_eq!(F::from_i32(4000).checked_mul(F::from_i32(4000)), None);
+    assert_eq!(F::from_i32(1).checked_div(F::ZERO), None);
+    assert_eq!(
+        F::from_i32(3).checked_mul(F::from_i32(-2)),
+        Some(F::from_i32(-6))
+    );
+    assert_eq!(F::from_i32(4000).saturating_mul(F::from_i32(-4000)), F::MIN);
+    assert_eq!(F::from_i32(-1).saturating_div(F::ZERO), F::MIN);
+    assert_eq!(F::MAX.wrapping_add(eps), F::MIN);
+    assert_eq!(
+        F::from_i32(7).wrapping_div(F::from_i32(2)),
+        F::from_float(3.5)
+    );
+}
+
+// a constant rotation integrated for a long time drifts in Q27 but not in
+// Fix64<56>
+#[test]
+fn long_integration() {
+    let step = 1e-4;
+    let steps = 100_000;
+    let axis = [0.36, -0.48, 0.8];
+    let rv = axis.map(|a| a * step);
+
+    let narrow_rv = Vec3 {
+        x: rv[0],
+        y: rv[1],
+        z: rv[2],
+    }
+    .map(|x| Fix::from_float(x as f32));
+    let wide_rv = narrow_rv.map(|x| x.to_fix64::<56>());
+    let dq = Quat::from_rvec(&narrow_rv);
+    let dq_wide = Quat::from_rvec(&wide_rv);
+    let mut narrow = Quat::<Fix>::default();
+    let mut wide = Quat::<Fix64<56>>::default();
+    for _ in 0..steps {
+        narrow = (narrow * dq).normalize_safe();
+        wide = (wide * dq_wide).normalize_safe();
+    }
+
+    // both integrate the same quantized rotation vector
+    let v = wide_rv.map(|x| x.to_float());
+    let theta = (v.x * v.x + v.y * v.y + v.z * v.z).sqrt() * steps as f64;
+    let (s, c) = (theta / 2.0).sin_cos();
+    let n = (v.x * v.x + v.y * v.y + v.z * v.z).sqrt();
+    let exact = [c, s * v.x / n, s * v.y / n, s * v.z / n];
+
+    let err = |q: [f64; 4]| {
+        q.iter()
+            .zip(exact)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max)
+    };
+    let narrow_err = err([narrow.w, narrow.x, narrow.y, narrow.z].map(|x| x.to_float() as f64));
+    let wide_err = err([wide.w, wide.x, wide.y, wide.z].map(|x| x.to_float()));
+    assert!(narrow_err > 1e-4, "{}", narrow_err);
+    assert!(wide_err < 1e-9, "{}", wide_err);
+}
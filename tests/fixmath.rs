@@ -0,0 +1,185 @@
+use ebin::fix32::Fix32;
+
+// Error of the elementary functions against f64 over the whole range of
+// Q27 and Q30, the bound documented in fix32.rs is 0.6 ulp.
+
+const BOUND: f64 = 0.6;
+
+fn ulp<const N: usize>() -> f64 {
+    1.0 / (1u64 << N) as f64
+}
+
+// raw values spread over the full i32 range plus everything close to `dense`
+fn inputs<const N: usize>(dense: &[f64]) -> Vec<Fix32<N>> {
+    let mut raw: Vec<i32> = (i32::MIN..=i32::MAX).step_by(10_007).collect();
+    raw.push(i32::MAX);
+    for &d in dense {
+        let c = (d / ulp::<N>()).round() as i64;
+        for i in -3000..=3000i64 {
+            let v = c + i * i.abs();
+            if v >= i32::MIN as i64 && v <= i32::MAX as i64 {
+                raw.push(v as i32);
+            }
+        }
+    }
+    raw.into_iter().map(Fix32::from_raw).collect()
+}
+
+fn check<const N: usize>(
+    name: &str,
+    xs: &[Fix32<N>],
+    f: impl Fn(Fix32<N>) -> Fix32<N>,
+    reference: impl Fn(f64) -> f64,
+) {
+    let ulp = ulp::<N>();
+    let (lo, hi) = (i32::MIN as f64 * ulp, i32::MAX as f64 * ulp);
+    let mut worst = (0.0, 0.0);
+    for &x in xs {
+        let xf = x.to_raw() as f64 * ulp;
+        let expected = reference(xf);
+        let expected = if expected.is_nan() {
+            continue;
+        } else {
+            expected.clamp(lo, hi)
+        };
+        let got = f(x).to_raw() as f64 * ulp;
+        let err = (got - expected).abs() / ulp;
+        if err > worst.0 {
+            worst = (err, xf);
+        }
+    }
+    assert!(
+        worst.0 <= BOUND,
+        "{} Q{}: error {} ulp at {}",
+        name,
+        N,
+        worst.0,
+        worst.1
+    );
+}
+
+fn recip<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0, -1.0]);
+    check("recip", &xs, |x| x.recip(), |x| 1.0 / x);
+}
+
+fn inv_sqrt<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0]);
+    let reference = |x: f64| {
+        if x <= 0.0 {
+            f64::INFINITY
+        } else {
+            1.0 / x.sqrt()
+        }
+    };
+    check("inv_sqrt", &xs, |x| x.inv_sqrt(), reference);
+}
+
+fn exp<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 2.77]);
+    check("exp", &xs, |x| x.exp(), f64::exp);
+}
+
+fn ln<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0]);
+    let reference = |x: f64| if x <= 0.0 { f64::NEG_INFINITY } else { x.ln() };
+    check("ln", &xs, |x| x.ln(), reference);
+}
+
+fn powi<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0, -1.0]);
+    for n in [-7, -3, -2, -1, 0, 1, 2, 3, 5, 8, 13, 100] {
+        let reference = |x: f64| {
+            let p = x.powi(n);
+            if p.is_finite() {
+                p
+            } else if x < 0.0 && n % 2 != 0 {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            }
+        };
+        check(&format!("powi({})", n), &xs, |x| x.powi(n), reference);
+    }
+}
+
+fn tan<const N: usize>() {
+    let pi = std::f64::consts::PI;
+    let xs = inputs::<N>(&[0.0, pi / 2.0, -pi / 2.0, 3.0 * pi / 2.0, pi]);
+    check("tan", &xs, |x| x.tan(), f64::tan);
+}
+
+fn atan<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0, -1.0, 0.2679]);
+    check("atan", &xs, |x| x.atan(), f64::atan);
+}
+
+fn asin_acos<const N: usize>() {
+    let xs = inputs::<N>(&[0.0, 1.0, -1.0, 0.5]);
+    let clamp = |x: f64| x.clamp(-1.0, 1.0);
+    check("asin", &xs, |x| x.asin(), |x| clamp(x).asin());
+    check("acos", &xs, |x| x.acos(), |x| clamp(x).acos());
+}
+
+macro_rules! q_format {
+    ($m:ident, $n:expr) => {
+        mod $m {
+            #[test]
+            fn recip() {
+                super::recip::<$n>();
+            }
+
+            #[test]
+            fn inv_sqrt() {
+                super::inv_sqrt::<$n>();
+            }
+
+            #[test]
+            fn exp() {
+                super::exp::<$n>();
+            }
+
+            #[test]
+            fn ln() {
+                super::ln::<$n>();
+            }
+
+            #[test]
+            fn powi() {
+                super::powi::<$n>();
+            }
+
+            #[test]
+            fn tan() {
+                super::tan::<$n>();
+            }
+
+            #[test]
+            fn atan() {
+                super::atan::<$n>();
+            }
+
+            #[test]
+            fn asin_acos() {
+                super::asin_acos::<$n>();
+            }
+        }
+    };
+}
+
+q_format!(q27, 27);
+q_format!(q30, 30);
+
+// Fewer fractional bits lose the bound for results far above 1, but
+// overflow still saturates with the right sign.
+#[test]
+fn powi_saturates_at_q16() {
+    let x = Fix32::<16>::from_float(-15862.7);
+    assert_eq!(x.powi(2), Fix32::MAX);
+    assert_eq!(x.powi(3), Fix32::MIN);
+    assert_eq!(x.powi(-1).to_raw(), -4);
+    let y = Fix32::<16>::from_float(0.001);
+    assert_eq!(y.powi(-2), Fix32::MAX);
+    assert_eq!((-y).powi(-3), Fix32::MIN);
+    assert_eq!(Fix32::<16>::from_float(181.0).powi(2).to_float(), 32761.0);
+}
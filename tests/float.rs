@@ -0,0 +1,72 @@
+use ebin::{
+    esplog,
+    float::{self, Encoder, Error},
+};
+
+// Float entry points: validation, quaternion and gyro streams.
+
+fn rates() -> Vec<[f32; 3]> {
+    (0..1000)
+        .map(|i| {
+            let t = i as f32 * 0.001;
+            [(t * 7.0).sin(), (t * 3.0).cos() * 2.0, -0.5]
+        })
+        .collect()
+}
+
+#[test]
+fn gyro_stream() {
+    let buf = float::encode_gyro(&rates(), 0.001, 12, 256).unwrap();
+    let log = esplog::decode(&buf).unwrap();
+    assert_eq!(log.quats.len(), 1000);
+    assert_eq!(log.time.len(), 4);
+    assert_eq!(log.time.iter().map(|t| t.dt_us).sum::<u32>(), 1_000_000);
+    assert_eq!(log.time[3].quats, 1000);
+
+    // the same orientations pushed as quaternions decode the same
+    let quats = float::decode_quats(&buf).unwrap();
+    let again = float::encode_quats(&quats, 12, 256).unwrap();
+    let back = float::decode_quats(&again).unwrap();
+    for (a, b) in quats.iter().zip(&back) {
+        let d = a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f32::max);
+        assert!(d < 1e-3);
+    }
+    // quaternions are normalized before conversion
+    let q = float::quat_from_f32([0.0, 0.0, 1.005, 0.0], 0).unwrap();
+    assert_eq!(float::quat_to_f32(&q), [0.0, 0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn rejects_bad_samples() {
+    assert_eq!(
+        float::encode_quats(&[[1.0, 0.0, 0.0, 0.0], [f32::NAN, 0.0, 0.0, 0.0]], 12, 16),
+        Err(Error::NotFinite { index: 1 })
+    );
+    assert_eq!(
+        float::quat_from_f32([0.5, 0.5, 0.0, 0.0], 7),
+        Err(Error::NotNormalized {
+            index: 7,
+            norm: 0.5f32.sqrt()
+        })
+    );
+    assert!(matches!(
+        float::encode_gyro(&[[0.0; 3], [4000.0, 0.0, 0.0]], 0.001, 12, 16),
+        Err(Error::RotationTooLarge { index: 1, .. })
+    ));
+    assert_eq!(
+        float::rvec_from_rate([f32::INFINITY, 0.0, 0.0], 0.001, 3).err(),
+        Some(Error::NotFinite { index: 3 })
+    );
+    assert_eq!(
+        Encoder::new(32, 16).err(),
+        Some(Error::Log(esplog::Error::InvalidQp { qp: 32 }))
+    );
+    assert_eq!(
+        float::decode_quats(b"EspLig2"),
+        Err(Error::Log(esplog::Error::BadMagic))
+    );
+}
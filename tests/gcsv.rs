@@ -0,0 +1,155 @@
+mod common;
+
+use std::path::Path;
+
+use ebin::{
+    esplog::{self, Log, Writer},
+    gcsv::{self, Error, Gcsv, MAGIC},
+    metadata,
+    orientation::ImuOrientation,
+};
+
+// Gyroflow IMU log parsing, line-numbered errors and export of decoded logs.
+
+fn parse_err(text: &str) -> (usize, String) {
+    match gcsv::parse(text.as_bytes()) {
+        Err(Error::Parse { line, msg }) => (line, msg),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[test]
+fn parse_test_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/test.gcsv");
+    let g = gcsv::read_file(path).unwrap();
+    let h = &g.header;
+    assert_eq!(h.version, "1.1");
+    assert_eq!(h.id, "esplog");
+    assert_eq!(h.orientation, ImuOrientation::parse("YxZ"));
+    assert_eq!((h.tscale, h.gscale), (0.0018, 0.000_532_632_21));
+    assert_eq!((h.ascale, h.mscale), (Some(0.0001), None));
+    assert_eq!(g.samples.len(), 64775 - 8);
+
+    let s = g.samples[0];
+    assert_eq!(s.t, 0.0018);
+    assert_eq!(s.gyro, [506.0, 1393.0, 633.0].map(|x| x * h.gscale));
+    assert_eq!(s.accel, Some([-9494.0, 86.0, -3138.0].map(|x| x * 0.0001)));
+    assert_eq!(s.mag, None);
+    assert_eq!(g.to_quats().len(), g.samples.len());
+}
+
+#[test]
+fn columns_in_any_order() {
+    let text = format!(
+        "{}\nvendor,x\ntscale,1\ngscale,0.5\nascale,2\nmscale,3\n\
+         t,mx,my,mz,az,ay,ax,gz,gy,gx\n1,1,2,3,4,5,6,7,8,9\n\n2.5,0,0,0,0,0,0,0,0,-1\n",
+        MAGIC
+    );
+    let g = gcsv::parse(text.as_bytes()).unwrap();
+    assert_eq!(g.header.extra, [("vendor".to_owned(), "x".to_owned())]);
+    assert_eq!(g.samples.len(), 2);
+    assert_eq!(g.samples[0].gyro, [4.5, 4.0, 3.5]);
+    assert_eq!(g.samples[0].accel, Some([12.0, 10.0, 8.0]));
+    assert_eq!(g.samples[0].mag, Some([3.0, 6.0, 9.0]));
+    assert_eq!(g.samples[1].t, 2.5);
+    assert_eq!(g.samples[1].gyro, [-0.5, 0.0, 0.0]);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(parse_err("GYROFLOW LOG\n").0, 1);
+    assert_eq!(parse_err("").0, 1);
+    let head = format!("{}\ntscale,0.001\ngscale,1\n", MAGIC);
+    assert_eq!(parse_err(&head), (4, "missing column header".to_owned()));
+    assert_eq!(
+        parse_err(&format!("{}\ntscale,fast\n", MAGIC)),
+        (2, "invalid tscale \"fast\"".to_owned())
+    );
+    assert_eq!(
+        parse_err(&format!("{}\ngscale,1\nt,gx,gy,gz\n", MAGIC)),
+        (3, "missing tscale".to_owned())
+    );
+    assert_eq!(
+        parse_err(&format!("{}t,gx,gy\n", head)),
+        (4, "missing gyro columns".to_owned())
+    );
+    assert_eq!(
+        parse_err(&format!("{}t,gx,gy,gz,ax,ay,az\n", head)),
+        (4, "missing ascale".to_owned())
+    );
+    assert_eq!(
+        parse_err(&format!("{}t,gx,gy,gz\n1,2,3,4\n2,3,x,5\n", head)),
+        (6, "invalid number in \"2,3,x,5\"".to_owned())
+    );
+    assert_eq!(
+        parse_err(&format!("{}t,gx,gy,gz\n1,2,3\n", head)),
+        (5, "expected 4 fields, got 3".to_owned())
+    );
+}
+
+fn recorded_log(accel_range: u8) -> Log {
+    let quats = common::input();
+    let mut w = Writer::new(14).unwrap();
+    w.time_offset(1000);
+    w.imu_orientation(ImuOrientation::parse("YxZ").unwrap());
+    w.accel_setup(10, accel_range);
+    for chunk in quats[..300].chunks(100) {
+        w.gyro_data(chunk).unwrap();
+        w.accel_data(&[[0, 4096, -8192]; 10]).unwrap();
+        w.time(100_000);
+    }
+    esplog::decode(&w.finish()).unwrap()
+}
+
+#[test]
+fn export_round_trip() {
+    let log = recorded_log(3);
+    let g = Gcsv::from_log(&log).unwrap();
+    assert_eq!(g.header.orientation, ImuOrientation::parse("YxZ"));
+    assert_eq!(g.header.gscale, gcsv::DEFAULT_GSCALE);
+    assert_eq!(g.header.ascale, Some(8.0 / 32768.0));
+    assert_eq!(g.samples.len(), 300);
+    assert_eq!(g.samples[0].t, 0.002);
+    assert_eq!(g.samples[299].t, 0.301);
+    assert_eq!(g.samples[0].accel, Some([0.0, 1.0, -2.0]));
+
+    let mut text = vec![];
+    g.write(&mut text).unwrap();
+    let back = gcsv::parse(&text[..]).unwrap();
+    assert_eq!(back.header.orientation, g.header.orientation);
+    assert_eq!(back.samples.len(), 300);
+    for (a, b) in back.samples.iter().zip(&g.samples) {
+        assert!((a.t - b.t).abs() < 1e-9);
+        for i in 0..3 {
+            assert!((a.gyro[i] - b.gyro[i]).abs() <= g.header.gscale / 2.0);
+        }
+        assert_eq!(a.accel, b.accel);
+    }
+
+    // integrating the rates gives back the decoded orientation
+    let quats = back.to_quats();
+    let (q, r) = (quats[299], log.quats[299]);
+    for (a, b) in [(q.w, r.w), (q.x, r.x), (q.y, r.y), (q.z, r.z)] {
+        assert!(
+            (a.to_float() - b.to_float()).abs() < 0.01,
+            "{:?} {:?}",
+            q,
+            r
+        );
+    }
+}
+
+#[test]
+fn export_errors() {
+    assert!(matches!(
+        Gcsv::from_log(&recorded_log(40)),
+        Err(Error::InvalidAccelRange { range: 40 })
+    ));
+    let mut log = recorded_log(3);
+    log.time.clear();
+    assert!(matches!(Gcsv::from_log(&log), Err(Error::NoTimestamps)));
+    log.imu_orientation = Some(*b"XXX");
+    log.metadata
+        .set(metadata::SAMPLE_RATE, metadata::Value::Float(1000.0));
+    assert!(matches!(Gcsv::from_log(&log), Err(Error::Log(_))));
+}
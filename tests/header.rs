@@ -0,0 +1,151 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// include/ebin.h is generated from src/lib/ffi.rs and checked in. Only the
+// subset of Rust used there is understood: i32 constants, opaque structs,
+// `//` comments and extern "C" functions taking integers, floats and
+// pointers. After changing the C interface the header is regenerated with
+//   cargo test --test header -- --ignored regenerate
+
+fn path(file: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(file)
+}
+
+fn source() -> String {
+    fs::read_to_string(path("src/lib/ffi.rs")).unwrap()
+}
+
+fn c_type(t: &str) -> String {
+    let t = t.trim();
+    if let Some(inner) = t.strip_prefix("*const ") {
+        return format!("const {} *", c_type(inner).trim_end());
+    }
+    if let Some(inner) = t.strip_prefix("*mut ") {
+        let inner = c_type(inner);
+        return if inner.ends_with('*') {
+            format!("{}*", inner)
+        } else {
+            format!("{} *", inner)
+        };
+    }
+    match t {
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        "i8" => "int8_t",
+        "i16" => "int16_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        "usize" => "size_t",
+        "isize" => "ptrdiff_t",
+        "f32" => "float",
+        "f64" => "double",
+        "" => "void",
+        other => other,
+    }
+    .to_string()
+}
+
+fn declaration(sig: &str) -> String {
+    // `name(args) -> ret`
+    let open = sig.find('(').unwrap();
+    let close = sig.rfind(')').unwrap();
+    let name = sig[..open].trim();
+    let ret = sig[close + 1..].trim().trim_start_matches("->");
+    let args: Vec<String> = sig[open + 1..close]
+        .split(',')
+        .filter(|a| !a.trim().is_empty())
+        .map(|a| {
+            let (n, t) = a.split_once(':').unwrap();
+            let t = c_type(t);
+            let sep = if t.ends_with('*') { "" } else { " " };
+            format!("{}{}{}", t, sep, n.trim())
+        })
+        .collect();
+    let args = if args.is_empty() {
+        "void".to_string()
+    } else {
+        args.join(", ")
+    };
+    let ret = c_type(ret);
+    let sep = if ret.ends_with('*') { "" } else { " " };
+    format!("{}{}{}({});", ret, sep, name, args)
+}
+
+fn generate(src: &str) -> String {
+    let mut out = String::new();
+    out += "// Generated from src/lib/ffi.rs by tests/header.rs, do not edit.\n\n";
+    out += "#ifndef EBIN_H\n#define EBIN_H\n\n";
+    out += "#include <stddef.h>\n#include <stdint.h>\n\n";
+    out += "#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n";
+
+    let mut comment = String::new();
+    let mut lines = src.lines();
+    let mut in_items = false;
+    let mut seen_items = false;
+    while let Some(line) = lines.next() {
+        let l = line.trim();
+        if let Some(c) = l.strip_prefix("//") {
+            comment += &format!("//{}\n", c);
+            continue;
+        }
+        if l.starts_with('#') {
+            continue;
+        }
+        if let Some(rest) = l.strip_prefix("pub const ") {
+            // pub const NAME: i32 = VALUE;
+            let (name, rest) = rest.split_once(':').unwrap();
+            let value = rest.split_once('=').unwrap().1.trim().trim_end_matches(';');
+            out += &comment;
+            out += &format!("#define {} ({})\n", name.trim(), value);
+            in_items = true;
+        } else if let Some(rest) = l.strip_prefix("pub struct ") {
+            let name = rest.trim_end_matches('{').trim();
+            if in_items {
+                out += "\n";
+            }
+            out += &comment;
+            out += &format!("typedef struct {0} {0};\n\n", name);
+            in_items = false;
+        } else if l.contains("extern \"C\" fn ") {
+            let mut sig = l.split("fn ").nth(1).unwrap().to_string();
+            while !sig.contains('{') {
+                sig += lines.next().unwrap().trim();
+            }
+            let sig = sig.split('{').next().unwrap().replace(",)", ")");
+            out += &comment;
+            out += &declaration(&sig);
+            out += "\n\n";
+        } else if l.is_empty() && in_items && comment.is_empty() {
+            out += "\n";
+            in_items = false;
+        } else if l.is_empty() && !seen_items && !comment.is_empty() {
+            // the module comment documents the whole header
+            out += &comment;
+            out += "\n";
+        }
+        seen_items |= l.starts_with("pub ");
+        comment.clear();
+    }
+
+    out += "#ifdef __cplusplus\n}\n#endif\n\n#endif // EBIN_H\n";
+    out
+}
+
+#[test]
+fn header_is_current() {
+    let header = fs::read_to_string(path("include/ebin.h")).unwrap();
+    assert!(
+        header == generate(&source()),
+        "include/ebin.h is stale, regenerate it"
+    );
+}
+
+#[test]
+#[ignore]
+fn regenerate() {
+    fs::write(path("include/ebin.h"), generate(&source())).unwrap();
+}
@@ -0,0 +1,110 @@
+use ebin::{
+    esplog::{self, Error, Writer, BLK_METADATA},
+    metadata::{self, Metadata, Value},
+};
+
+// Metadata blocks written by the writer and read back by the decoder.
+
+fn sample() -> Metadata {
+    let mut m = Metadata::new();
+    m.set(metadata::DEVICE_MODEL, Value::Str("esp32-s3 logger".into()));
+    m.set(metadata::SAMPLE_RATE, Value::Int(1000));
+    m.set(metadata::GYRO_SCALE, Value::Float(0.000_532_632_2));
+    m.set(metadata::TIME_SCALE, Value::Float(0.0018));
+    m
+}
+
+#[test]
+fn round_trip() {
+    let mut w = Writer::new(14).unwrap();
+    w.metadata(&sample()).unwrap();
+    let log = esplog::decode(&w.finish()).unwrap();
+    assert_eq!(log.metadata, sample());
+    assert_eq!(log.metadata.gyro_scale(), Some(0.000_532_632_2));
+    assert_eq!(log.metadata.sample_rate(), Some(1000.0));
+    assert_eq!(log.metadata.time_scale(), Some(0.0018));
+    assert_eq!(log.metadata.accel_scale(), None);
+    assert_eq!(
+        log.metadata
+            .get(metadata::DEVICE_MODEL)
+            .and_then(Value::as_str),
+        Some("esp32-s3 logger")
+    );
+}
+
+#[test]
+fn many_entries_and_later_blocks_win() {
+    let mut big = Metadata::new();
+    for i in 0..600 {
+        big.set(&format!("key{}", i), Value::Int(i));
+    }
+    let mut update = Metadata::new();
+    update.set("key7", Value::Str("seven".into()));
+
+    let mut w = Writer::new(14).unwrap();
+    w.metadata(&big).unwrap();
+    w.metadata(&update).unwrap();
+    let buf = w.finish();
+    let blocks = esplog::scan(&buf).unwrap();
+    assert_eq!(blocks.iter().filter(|b| b.id == BLK_METADATA).count(), 4);
+
+    let log = esplog::decode(&buf).unwrap();
+    assert_eq!(log.metadata.len(), 600);
+    assert_eq!(log.metadata.get("key599"), Some(&Value::Int(599)));
+    assert_eq!(
+        log.metadata.get("key7").and_then(Value::as_str),
+        Some("seven")
+    );
+}
+
+#[test]
+fn too_large_entry_writes_nothing() {
+    let mut m = Metadata::new();
+    for i in 0..300 {
+        m.set(&format!("key{}", i), Value::Int(i));
+    }
+    // in the second block, after the first one has been serialized
+    m.set(&"k".repeat(300), Value::Int(0));
+
+    let mut w = Writer::new(14).unwrap();
+    w.time(1000);
+    assert_eq!(w.metadata(&m), Err(Error::BlockTooLarge { len: 301 }));
+    let mut long_str = Metadata::new();
+    long_str.set(metadata::LENS_PROFILE, Value::Str("x".repeat(70_000)));
+    assert!(w.metadata(&long_str).is_err());
+    w.time(1000);
+
+    // same bytes as a writer that never tried
+    let mut clean = Writer::new(14).unwrap();
+    clean.time(1000);
+    clean.time(1000);
+    let buf = w.finish();
+    assert_eq!(buf, clean.finish());
+    let log = esplog::decode(&buf).unwrap();
+    assert!(log.metadata.is_empty());
+    assert_eq!(log.time.len(), 2);
+}
+
+#[test]
+fn corrupt_block() {
+    let mut w = Writer::new(14).unwrap();
+    w.metadata(&sample()).unwrap();
+    let buf = w.finish();
+    let pos = esplog::HEADER_SIZE;
+
+    // entry count larger than the block
+    let mut bad = buf.clone();
+    bad[pos + 1] = 200;
+    assert_eq!(esplog::decode(&bad), Err(Error::CorruptBlock { pos }));
+
+    // unknown value type
+    let mut bad = buf.clone();
+    let klen = bad[pos + 2] as usize;
+    bad[pos + 3 + klen] = 0x7f;
+    assert_eq!(esplog::decode(&bad), Err(Error::CorruptBlock { pos }));
+
+    assert_eq!(
+        esplog::decode(&buf[..buf.len() - 1]),
+        Err(Error::CorruptBlock { pos })
+    );
+}
@@ -0,0 +1,65 @@
+use ebin::{
+    metrics::{self, ErrorStats},
+    quat::{Fix, Quat, RVec},
+};
+
+// Angular error statistics against rotations of known size.
+
+fn rot(axis: usize, deg: f64) -> Quat {
+    let h = deg.to_radians() / 2.0;
+    let mut v = [0.0; 3];
+    v[axis] = h.sin();
+    let f = |x: f64| Fix::from_float(x as f32);
+    Quat::new(f(h.cos()), f(v[0]), f(v[1]), f(v[2]))
+}
+
+#[test]
+fn known_errors() {
+    let a = rot(2, 30.0);
+    let b = a * rot(0, 2.0);
+    let e = metrics::error_rvec(&a, &b);
+    assert!((e[0] - 2.0).abs() < 1e-4 && e[1].abs() < 1e-4 && e[2].abs() < 1e-4);
+    assert!((metrics::angular_error(&b, &a) - 2.0).abs() < 1e-4);
+    // q and -q are the same rotation
+    let neg = Quat::new(-b.w, -b.x, -b.y, -b.z);
+    assert!((metrics::angular_error(&a, &neg) - 2.0).abs() < 1e-4);
+    assert_eq!(metrics::angular_error(&a, &a), 0.0);
+
+    // errors of 0, 1, 2 and 3 degrees in blocks of two
+    let original = vec![Quat::default(); 4];
+    let decoded: Vec<Quat> = (0..4).map(|i| rot(1, i as f64)).collect();
+    let r = metrics::compare(&original, &decoded, 2);
+    let o = r.overall;
+    assert_eq!((o.count, o.max_index), (4, 3));
+    assert!((o.max - 3.0).abs() < 1e-4);
+    assert!((o.mean - 1.5).abs() < 1e-4);
+    assert!((o.rmse - 3.5f64.sqrt()).abs() < 1e-4);
+    assert!(r.axis_rmse[0] < 1e-4 && r.axis_rmse[2] < 1e-4);
+    assert!((r.axis_rmse[1] - o.rmse).abs() < 1e-9);
+    assert!((r.axis_max[1] - 3.0).abs() < 1e-4);
+    assert_eq!(r.blocks.len(), 2);
+    assert_eq!((r.blocks[1].count, r.blocks[1].max_index), (2, 3));
+    assert!((r.percentile(50.0) - 1.0).abs() < 1e-4);
+    assert!((r.percentile(100.0) - 3.0).abs() < 1e-4);
+    assert_eq!(r.percentile(0.0), r.percentile(1.0));
+}
+
+#[test]
+fn degenerate_input() {
+    // empty and unequal streams compare the common prefix only
+    let r = metrics::compare(&[], &[Quat::default()], 16);
+    assert_eq!(r.overall, ErrorStats::default());
+    assert!(r.blocks.is_empty());
+    assert_eq!(r.percentile(99.0), 0.0);
+    assert_eq!(r.axis_rmse, [0.0; 3]);
+
+    let q = Quat::from_rvec(&RVec::new(
+        Fix::from_float(0.1),
+        Fix::from_float(0.0),
+        Fix::from_float(0.0),
+    ));
+    let r = metrics::compare(&[q, q, q], &[q], 0);
+    assert_eq!(r.overall.count, 1);
+    assert_eq!(r.overall.max, 0.0);
+    assert!(r.blocks.is_empty());
+}
@@ -0,0 +1,135 @@
+use ebin::{
+    esplog::{self, Error, Log, Writer},
+    gcsv,
+    orientation::ImuOrientation,
+    quat::{Fix, Quat},
+};
+
+// IMU axis remaps, proper and mirrored, against matrix conjugation.
+
+type M = [[f64; 3]; 3];
+
+fn mat_mul(a: M, b: M) -> M {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn transpose(a: M) -> M {
+    let mut m = a;
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = a[j][i];
+        }
+    }
+    m
+}
+
+fn to_f64(q: &Quat) -> M {
+    q.to_matrix().map(|r| r.map(|x| x.to_float() as f64))
+}
+
+fn all() -> Vec<ImuOrientation> {
+    let mut v = vec![];
+    for p in ["XYZ", "XZY", "YXZ", "YZX", "ZXY", "ZYX"] {
+        for flips in 0..8 {
+            let s: String = p
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    if flips & (1 << i) != 0 {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+            v.push(ImuOrientation::parse(&s).unwrap());
+        }
+    }
+    v
+}
+
+#[test]
+fn remaps_conjugate_rotations() {
+    let q = Quat::new(
+        Fix::from_float(0.8),
+        Fix::from_float(0.1),
+        Fix::from_float(-0.5),
+        Fix::from_float(0.3),
+    )
+    .normalize_safe();
+    let all = all();
+    assert_eq!(all.iter().filter(|o| o.is_mirrored()).count(), 24);
+    for o in all {
+        let m = o.to_matrix().map(|r| r.map(|x| x as f64));
+        let expected = mat_mul(mat_mul(m, to_f64(&q)), transpose(m));
+        let got = to_f64(&o.remap_quat(&q));
+        for (a, b) in got.iter().flatten().zip(expected.iter().flatten()) {
+            assert!((a - b).abs() < 1e-5, "{}", o);
+        }
+        // to_quat conjugates the same way, mirror or not
+        let r = o.to_quat();
+        let conj = to_f64(&(r * q * r.conj()));
+        for (a, b) in conj.iter().flatten().zip(expected.iter().flatten()) {
+            assert!((a - b).abs() < 1e-5, "{}", o);
+        }
+        // accel is an ordinary vector
+        assert_eq!(o.remap_i16([1, 2, 3]).map(|x| x as f64), {
+            let v = [1.0, 2.0, 3.0];
+            [0, 1, 2].map(|i| (0..3).map(|j| m[i][j] * v[j]).sum::<f64>())
+        });
+    }
+}
+
+#[test]
+fn mirrored_in_logs_and_gcsv() {
+    let o = ImuOrientation::parse("xYZ").unwrap();
+    assert!(o.is_mirrored());
+
+    let mut w = Writer::new(14).unwrap();
+    w.imu_orientation(o);
+    let mut log = esplog::decode(&w.finish()).unwrap();
+    assert_eq!(log.orientation(), Ok(Some(o)));
+    log.accels.push([5, 6, i16::MIN]);
+    log.to_camera_frame().unwrap();
+    assert_eq!(log.accels, [[-5, 6, i16::MIN]]);
+    assert_eq!(log.orientation(), Ok(Some(ImuOrientation::IDENTITY)));
+
+    let text = format!(
+        "{}\norientation,xYZ\ntscale,0.001\ngscale,1\nt,gx,gy,gz\n0,1,2,3\n",
+        gcsv::MAGIC
+    );
+    let g = gcsv::parse(text.as_bytes()).unwrap();
+    assert_eq!(g.header.orientation, Some(o));
+}
+
+#[test]
+fn invalid() {
+    for s in ["XXZ", "XYW", "xyzz", "XY", "", "xYy"] {
+        assert_eq!(ImuOrientation::parse(s), None, "{}", s);
+    }
+    let log = Log {
+        imu_orientation: Some(*b"ZZz"),
+        ..Log::default()
+    };
+    assert_eq!(
+        log.orientation(),
+        Err(Error::InvalidOrientation {
+            orientation: *b"ZZz"
+        })
+    );
+
+    let text = format!(
+        "{}\norientation,XZZ\ntscale,0.001\ngscale,1\nt,gx,gy,gz\n",
+        gcsv::MAGIC
+    );
+    match gcsv::parse(text.as_bytes()) {
+        Err(gcsv::Error::Parse { line: 2, .. }) => {}
+        r => panic!("{:?}", r),
+    }
+}
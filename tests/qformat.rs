@@ -0,0 +1,165 @@
+use ebin::{fix32::Fix32, fix64::Fix64, quat::Fix};
+
+fn raw_values() -> Vec<i32> {
+    let mut v: Vec<i32> = (i32::MIN..=i32::MAX).step_by(65_537).collect();
+    v.extend([i32::MAX, -1, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, -7, -8, -9]);
+    v
+}
+
+// reference conversion in f64, ties away from zero, saturating
+fn reference(raw: i64, from: i32, to: i32, lo: f64, hi: f64) -> f64 {
+    let x = raw as f64 * 2f64.powi(to - from);
+    x.round().clamp(lo, hi)
+}
+
+#[test]
+fn convert_rounds_and_saturates() {
+    let (lo, hi) = (i32::MIN as f64, i32::MAX as f64);
+    for raw in raw_values() {
+        let x = Fix::from_raw(raw);
+        let r = raw as i64;
+        assert_eq!(
+            x.convert::<16>().to_raw() as f64,
+            reference(r, 27, 16, lo, hi)
+        );
+        assert_eq!(
+            x.convert::<30>().to_raw() as f64,
+            reference(r, 27, 30, lo, hi)
+        );
+        assert_eq!(
+            x.convert::<0>().to_raw() as f64,
+            reference(r, 27, 0, lo, hi)
+        );
+        assert_eq!(x.convert::<27>(), x);
+
+        let fits = |v: f64| v >= lo && v <= hi;
+        assert_eq!(
+            x.checked_convert::<30>().is_some(),
+            fits(raw as f64 * 8.0),
+            "{}",
+            raw
+        );
+    }
+    assert_eq!(Fix::from_raw(4).convert::<25>().to_raw(), 1);
+    assert_eq!(Fix::from_raw(2).convert::<25>().to_raw(), 1);
+    assert_eq!(Fix::from_raw(-2).convert::<25>().to_raw(), -1);
+    assert_eq!(Fix::from_raw(1).convert::<25>().to_raw(), 0);
+    assert_eq!(Fix::from_i32(15).convert::<30>(), Fix32::<30>::MAX);
+    assert_eq!(Fix::from_i32(-15).convert::<30>(), Fix32::<30>::MIN);
+}
+
+#[test]
+fn exact_convert() {
+    let x = Fix::from_float(1.25);
+    assert_eq!(x.exact_convert::<16>(), Some(Fix32::<16>::from_float(1.25)));
+    assert_eq!(x.exact_convert::<30>(), Some(Fix32::<30>::from_float(1.25)));
+    assert_eq!(Fix::from_raw(1).exact_convert::<16>(), None);
+    assert_eq!(Fix::from_i32(4).exact_convert::<30>(), None);
+    for raw in raw_values() {
+        let x = Fix::from_raw(raw);
+        if let Some(y) = x.exact_convert::<20>() {
+            assert_eq!(y.convert::<27>(), x);
+        }
+        // widening to Fix64 never loses anything
+        assert_eq!(x.to_fix64::<40>().to_fix32::<27>(), x);
+        assert_eq!(Fix64::from(x).checked_to_fix32::<27>(), Some(x));
+    }
+}
+
+#[test]
+fn fix64_convert() {
+    let (lo, hi) = (i64::MIN as f64, i64::MAX as f64);
+    for raw in raw_values() {
+        let r = raw as i64 * 1_000_003;
+        let x = Fix64::<40>::from_raw(r);
+        assert_eq!(
+            x.convert::<20>().to_raw() as f64,
+            reference(r, 40, 20, lo, hi)
+        );
+        assert_eq!(
+            x.convert::<60>().to_raw() as f64,
+            reference(r, 40, 60, lo, hi)
+        );
+        let narrow = reference(r, 40, 27, i32::MIN as f64, i32::MAX as f64);
+        assert_eq!(x.to_fix32::<27>().to_raw() as f64, narrow);
+    }
+    assert_eq!(Fix64::<40>::from_i32(20).checked_to_fix32::<27>(), None);
+    assert_eq!(Fix64::<40>::from_i32(-20).to_fix32::<27>(), Fix::MIN);
+    assert_eq!(Fix64::<10>::from_i32(1 << 20).checked_convert::<50>(), None);
+}
+
+#[test]
+fn mixed_multiply() {
+    let gains = [0.5, -1.75, 3.0001, 100.25, -0.0001];
+    let samples = [0.0, 1.0, -0.3, 7.9, -15.99, 0.000_001];
+    for &g in &gains {
+        let gain = Fix32::<16>::from_float(g as f32);
+        for &s in &samples {
+            let sample = Fix::from_float(s as f32);
+            let exact = gain.to_float() as f64 * sample.to_float() as f64;
+
+            let p: Fix = sample.mul_mixed(gain);
+            let expected = (exact * (1u64 << 27) as f64).round();
+            let expected = expected.clamp(i32::MIN as f64, i32::MAX as f64);
+            assert_eq!(p.to_raw() as f64, expected, "{} * {}", s, g);
+
+            // 27 + 16 fractional bits hold the product exactly
+            let w: Fix64<43> = sample.mul_fix64(gain);
+            assert_eq!(w.to_float(), exact);
+
+            let acc = Fix64::<40>::from_float(s);
+            let a: Fix64<40> = acc.mul_mixed(gain);
+            let err = (a.to_float() - acc.to_float() * gain.to_float() as f64).abs();
+            assert!(err <= 0.5 / (1u64 << 40) as f64, "{} * {}", s, g);
+        }
+    }
+}
+
+// summing many small increments loses precision in Q27 but not in Q50
+#[test]
+fn wide_accumulator() {
+    let step = Fix::from_raw(3);
+    let k = Fix32::<30>::from_float(0.3);
+    let mut narrow = Fix::ZERO;
+    let mut wide = Fix64::<50>::ZERO;
+    for _ in 0..100_000 {
+        narrow = narrow + step.mul_mixed(k);
+        wide = wide + step.mul_fix64(k);
+    }
+    let exact = 100_000.0 * 3.0 * k.to_float() as f64 / (1u64 << 27) as f64;
+    let narrow_err = (narrow.to_float() as f64 - exact).abs();
+    let wide_err = (wide.to_fix32::<27>().to_raw() as f64 / (1u64 << 27) as f64 - exact).abs();
+    assert!(narrow_err > 1e-5);
+    assert!(wide_err <= 0.5 / (1u64 << 27) as f64);
+}
+
+#[test]
+fn fix64_operators() {
+    let a = Fix64::<40>::from_float(3.25);
+    let b = Fix64::<40>::from_float(-0.125);
+    assert_eq!((a + b).to_float(), 3.125);
+    assert_eq!((a - b).to_float(), 3.375);
+    assert_eq!((a * b).to_float(), -0.40625);
+    assert_eq!((a / b).to_float(), -26.0);
+    assert_eq!((-a).to_float(), -3.25);
+    assert_eq!(
+        Fix64::<40>::from_raw(1) * Fix64::from_float(0.5),
+        Fix64::from_raw(1)
+    );
+    assert_eq!(
+        Fix64::<40>::from_raw(-1) * Fix64::from_float(0.5),
+        Fix64::from_raw(-1)
+    );
+    assert_eq!(
+        Fix64::<40>::from_raw(1) / Fix64::from_i32(3),
+        Fix64::from_raw(0)
+    );
+    assert_eq!(
+        Fix64::<40>::from_raw(2) / Fix64::from_i32(3),
+        Fix64::from_raw(1)
+    );
+    assert_eq!(
+        Fix64::<40>::from_raw(-2) / Fix64::from_i32(3),
+        Fix64::from_raw(-1)
+    );
+}
@@ -0,0 +1,195 @@
+use ebin::{
+    quat::{Fix, Quat, Vec3},
+    scalar::Scalar,
+};
+
+// Quaternion toolkit in fixed point and f64 against reference formulas in
+// f64. The fixed point bound comes from the sin/cos/atan2 approximations.
+
+const FIX_TOL: f64 = 2e-3;
+const F64_TOL: f64 = 1e-9;
+
+type Q = [f64; 4];
+
+fn quats() -> Vec<Q> {
+    let mut seed = 0x1234_5678u32;
+    let mut next = move || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 8) as f64 / (1u32 << 24) as f64 * 2.0 - 1.0
+    };
+    let mut v: Vec<Q> = (0..300)
+        .map(|_| normalize([next(), next(), next(), next()]))
+        .collect();
+    v.push([1.0, 0.0, 0.0, 0.0]);
+    v.push([0.0, 1.0, 0.0, 0.0]);
+    v.push(normalize([0.3, -0.2, 0.9, 0.1]));
+    v
+}
+
+fn normalize(q: Q) -> Q {
+    let n = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+    q.map(|x| x / n)
+}
+
+fn mul(a: Q, b: Q) -> Q {
+    [
+        a[0] * b[0] - a[1] * b[1] - a[2] * b[2] - a[3] * b[3],
+        a[0] * b[1] + a[1] * b[0] + a[2] * b[3] - a[3] * b[2],
+        a[0] * b[2] - a[1] * b[3] + a[2] * b[0] + a[3] * b[1],
+        a[0] * b[3] + a[1] * b[2] - a[2] * b[1] + a[3] * b[0],
+    ]
+}
+
+fn dot(a: Q, b: Q) -> f64 {
+    (0..4).map(|i| a[i] * b[i]).sum()
+}
+
+// half the rotation angle of q, acos(w) loses precision near 0
+fn half_angle(q: Q) -> f64 {
+    let s = (q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    s.atan2(q[0].abs())
+}
+
+fn conj(q: Q) -> Q {
+    [q[0], -q[1], -q[2], -q[3]]
+}
+
+fn slerp(a: Q, b: Q, t: f64) -> Q {
+    let b = if dot(a, b) < 0.0 { b.map(|x| -x) } else { b };
+    let theta = half_angle(mul(conj(a), b));
+    if theta < 1e-9 {
+        return a;
+    }
+    let (ka, kb) = (
+        ((1.0 - t) * theta).sin() / theta.sin(),
+        (t * theta).sin() / theta.sin(),
+    );
+    [0, 1, 2, 3].map(|i| ka * a[i] + kb * b[i])
+}
+
+fn to<T: Scalar>(q: Q) -> Quat<T> {
+    Quat::new(q[0], q[1], q[2], q[3]).map(T::from_f64)
+}
+
+fn from<T: Scalar>(q: Quat<T>) -> Q {
+    [q.w, q.x, q.y, q.z].map(|x| x.to_f64())
+}
+
+// q and -q are the same rotation
+fn rot_diff(a: Q, b: Q) -> f64 {
+    let d = |s: f64| (0..4).map(|i| (a[i] - s * b[i]).abs()).fold(0.0, f64::max);
+    d(1.0).min(d(-1.0))
+}
+
+fn check<T: Scalar>(tol: f64) {
+    let qs = quats();
+    for (i, &a) in qs.iter().enumerate() {
+        let b = qs[(i * 7 + 3) % qs.len()];
+        let (qa, qb) = (to::<T>(a), to::<T>(b));
+
+        let inv = from(qa.smul(T::from_f64(1.5)).inverse());
+        let expected = conj(a).map(|x| x / 1.5);
+        assert!(rot_diff(inv, expected) < tol, "inverse {:?}", a);
+        assert!((qa.dot(&qb).to_f64() - dot(a, b)).abs() < tol);
+
+        let angle = 2.0 * half_angle(mul(conj(a), b));
+        let got = qa.angle_to(&qb).to_f64();
+        assert!((got - angle).abs() < tol, "angle_to {} {}", got, angle);
+
+        for t in [0.0, 0.25, 0.5, 0.9, 1.0] {
+            let s = from(qa.slerp(&qb, T::from_f64(t)));
+            assert!(rot_diff(s, slerp(a, b, t)) < tol, "slerp {} {:?}", t, a);
+            // nlerp follows the same great circle
+            let n = from(qa.nlerp(&qb, T::from_f64(t)));
+            let bb = if dot(a, b) < 0.0 { b.map(|x| -x) } else { b };
+            let lin = normalize([0, 1, 2, 3].map(|k| (1.0 - t) * a[k] + t * bb[k]));
+            assert!(rot_diff(n, lin) < tol, "nlerp {} {:?}", t, a);
+        }
+
+        // axis-angle and log/exp
+        let (axis, angle) = qa.to_axis_angle();
+        let back = from(Quat::from_axis_angle(&axis, angle));
+        assert!(rot_diff(back, a) < tol, "axis angle {:?}", a);
+        assert!((angle.to_f64() - 2.0 * half_angle(a)).abs() < tol);
+
+        let log = qa.log();
+        assert_eq!(log.w, T::ZERO);
+        let half = angle.to_f64() / 2.0;
+        for (l, x) in [(log.x, axis.x), (log.y, axis.y), (log.z, axis.z)] {
+            assert!((l.to_f64() - half * x.to_f64()).abs() < tol);
+        }
+        assert!(rot_diff(from(log.exp()), a) < tol, "exp {:?}", a);
+
+        // composing with the relative rotation lands on b
+        let rel = from(qa.inverse() * qb);
+        assert!(rot_diff(mul(a, rel), b) < tol);
+    }
+}
+
+#[test]
+fn fixed_point() {
+    check::<Fix>(FIX_TOL);
+}
+
+#[test]
+fn float() {
+    check::<f64>(F64_TOL);
+}
+
+#[test]
+fn operators() {
+    let a = to::<Fix>([0.5, -0.5, 0.5, 0.5]);
+    let b = to::<Fix>([0.8, 0.36, -0.48, 0.0]);
+    assert_eq!(a - b + b, a);
+    assert_eq!(-(-a), a);
+    assert_eq!(a - a, Quat::new(Fix::ZERO, Fix::ZERO, Fix::ZERO, Fix::ZERO));
+    // -a is the same rotation
+    let p = Vec3::new(0.3, -0.7, 0.2).map(Fix::from_f64);
+    let (r1, r2) = (a.rotate_point(&p), (-a).rotate_point(&p));
+    assert_eq!([r1.x, r1.y, r1.z], [r2.x, r2.y, r2.z]);
+    assert_eq!(a.angle_to(&-a), Fix::ZERO);
+}
+
+#[test]
+fn vec3_ops() {
+    let f = Fix::from_f64;
+    let a = Vec3::new(f(0.5), f(-0.25), f(1.0));
+    let b = Vec3::from([f(0.125), f(2.0), f(-0.75)]);
+
+    assert_eq!(a.dot(&b), f(0.0625 - 0.5 - 0.75));
+    let c = a.cross(&b);
+    assert_eq!(<[Fix; 3]>::from(c), [f(-1.8125), f(0.5), f(1.03125)]);
+    assert_eq!(c.dot(&a), Fix::ZERO);
+    assert_eq!(c.dot(&b), Fix::ZERO);
+    assert_eq!(b.abs_max(), f(2.0));
+    assert_eq!((-a).abs_max(), f(1.0));
+
+    let mut v = a;
+    v += b;
+    v -= a;
+    assert_eq!(<[Fix; 3]>::from(v), <[Fix; 3]>::from(b));
+    v *= f(2.0);
+    assert_eq!(<[Fix; 3]>::from(v), <[Fix; 3]>::from(b * f(2.0)));
+    v /= f(4.0);
+    assert_eq!(<[Fix; 3]>::from(v), <[Fix; 3]>::from(b / f(2.0)));
+
+    v[1] = f(3.0);
+    assert_eq!([v[0], v[1], v[2]], [f(0.0625), f(3.0), f(-0.375)]);
+
+    let n = a.normalized_safe();
+    assert!((n.norm().to_f64() - 1.0).abs() < 1e-6);
+    assert_eq!(
+        <[Fix; 3]>::from(Vec3::<Fix>::default().normalized_safe()),
+        [Fix::ZERO; 3]
+    );
+    assert_eq!(
+        <[f64; 3]>::from(Vec3::<f64>::default().normalized_safe()),
+        [0.0; 3]
+    );
+}
+
+#[test]
+#[should_panic]
+fn vec3_index_out_of_range() {
+    let _ = Vec3::new(1.0, 2.0, 3.0)[3];
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::io::ErrorKind;
+
+use ebin::{
+    quat::Fix,
+    rawquat::{self, HEADER_SIZE, MAGIC},
+};
+
+// .rawquat files with and without the header.
+
+fn with_q(q: u8, values: [i32; 4]) -> Vec<u8> {
+    let mut buf = MAGIC.to_vec();
+    buf.extend_from_slice(&[0x01, q, 0, 0]);
+    for v in values {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+    buf
+}
+
+#[test]
+fn round_trip() {
+    let quats = &common::input()[..100];
+    let plain = rawquat::encode(quats, false);
+    let headed = rawquat::encode(quats, true);
+    assert_eq!(plain.len(), 1600);
+    assert_eq!(&headed[..HEADER_SIZE], b"RAWQ\x01\x1b\x00\x00");
+    assert_eq!(headed[HEADER_SIZE..], plain[..]);
+    assert!(rawquat::decode(&plain).unwrap() == quats);
+    assert!(rawquat::decode(&headed).unwrap() == quats);
+    assert!(rawquat::decode(&[]).unwrap().is_empty());
+
+    let path = std::env::temp_dir().join(format!("ebin-{}.rawquat", std::process::id()));
+    rawquat::write_file(&path, quats, true).unwrap();
+    let back = rawquat::read_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(back.unwrap() == quats);
+}
+
+#[test]
+fn other_q_formats() {
+    // Q30 is rounded to nearest, Q16 is widened exactly
+    let q = rawquat::decode(&with_q(30, [1 << 30, 12, 11, -(1 << 29)])).unwrap();
+    assert_eq!(
+        [q[0].w, q[0].x, q[0].y, q[0].z].map(|x| x.to_raw()),
+        [1 << 27, 2, 1, -(1 << 26)]
+    );
+    let q = rawquat::decode(&with_q(16, [1 << 16, -3, 0, 0])).unwrap();
+    assert_eq!(q[0].w, Fix::from_raw(1 << 27));
+    assert_eq!(q[0].x.to_raw(), -3 << 11);
+}
+
+#[test]
+fn invalid() {
+    let err = |buf: &[u8]| rawquat::decode(buf).err().unwrap();
+    let mut buf = rawquat::encode(&common::input()[..100], true);
+    buf.pop();
+    assert_eq!(err(&buf).kind(), ErrorKind::InvalidData);
+    assert_eq!(err(b"RAWQ\x01").kind(), ErrorKind::InvalidData);
+    assert!(err(b"RAWQ\x02\x1b\x00\x00").to_string().contains("version"));
+    assert!(err(&with_q(32, [0; 4])).to_string().contains("Q-format"));
+    // Q8 values of 2^23 no longer fit into Q27
+    assert!(err(&with_q(8, [1 << 23, 0, 0, 0]))
+        .to_string()
+        .contains("range"));
+    assert_eq!(
+        rawquat::read_file("no/such/file.rawquat")
+            .err()
+            .unwrap()
+            .kind(),
+        ErrorKind::NotFound
+    );
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use ebin::{
+    fix64::Fix64,
+    quant::State,
+    quat::{Fix, Quat, Vec3},
+    scalar::Scalar,
+};
+
+// The quantization loop run with fixed point, Fix64 and float numbers on the
+// same input.
+
+const QP: u8 = 10;
+
+struct Run {
+    data: Vec<i8>,
+    decoded: Vec<[f64; 4]>,
+    max_ang_err: f64,
+}
+
+fn run<T: Scalar>(quats: &[Quat]) -> Run {
+    let quats: Vec<Quat<T>> = quats.iter().map(|q| q.map(|x| T::from_fix(x))).collect();
+    let mut data = vec![0; quats.len() * 30];
+    let res = State::<T>::new()
+        .quant_block(&quats, QP, &mut data)
+        .unwrap();
+    data.truncate(res.bytes_put);
+
+    let mut decoded = vec![Quat::<T>::default(); quats.len()];
+    let dec = State::<T>::new()
+        .dequant_block(&data, QP, &mut decoded)
+        .unwrap();
+    assert_eq!(dec.quats_put, quats.len());
+    assert!(dec.new_state.q == res.new_state.q);
+
+    Run {
+        data,
+        decoded: decoded
+            .iter()
+            .map(|q| [q.w, q.x, q.y, q.z].map(|x| x.to_f64()))
+            .collect(),
+        max_ang_err: res.max_ang_err.to_f64(),
+    }
+}
+
+fn max_diff(a: &Run, b: &Run) -> f64 {
+    a.decoded
+        .iter()
+        .zip(&b.decoded)
+        .flat_map(|(p, q)| (0..4).map(move |i| (p[i] - q[i]).abs()))
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn float_matches_fixed_point() {
+    let quats = common::input();
+    let fix = run::<Fix>(&quats);
+    let wide = run::<Fix64<40>>(&quats);
+    let single = run::<f32>(&quats);
+    let double = run::<f64>(&quats);
+
+    // same step size, so the streams only differ by the trigonometry
+    let step = (1 << QP) as f64 / (1 << 27) as f64;
+    for r in [&wide, &single, &double] {
+        let len = r.data.len() as f64 / fix.data.len() as f64;
+        assert!((0.99..=1.01).contains(&len), "{}", len);
+        assert!(max_diff(&fix, r) < 8.0 * step);
+        assert!(r.max_ang_err < 2.0 * step);
+    }
+    assert!(max_diff(&wide, &double) < 1e-9);
+}
+
+#[test]
+fn float_quat_ops() {
+    let a = Quat::new(0.8f64, 0.36, -0.48, 0.0);
+    let b = Quat::new(0.5f64, -0.5, 0.5, 0.5);
+    let af = a.map(Fix::from_f64);
+    let bf = b.map(Fix::from_f64);
+    let p = a * b;
+    let pf = af * bf;
+    for (x, y) in [(p.w, pf.w), (p.x, pf.x), (p.y, pf.y), (p.z, pf.z)] {
+        assert!((x - y.to_f64()).abs() < 1e-7);
+    }
+
+    let v = Vec3::new(0.3f64, -1.2, 0.05);
+    let back = Quat::from_rvec(&v).to_rvec();
+    assert!((back - v).norm() < 1e-12);
+    let rotated = b.rotate_point(&Vec3::new(1.0, 0.0, 0.0));
+    assert!((rotated.norm() - 1.0).abs() < 1e-12);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use ebin::{
+    esplog::Error,
+    sweep::{self, SweepPoint},
+};
+
+// Rate-distortion sweep over qp and block size.
+
+#[test]
+fn sweep_grid() {
+    let quats = &common::input()[..1000];
+    let points = sweep::sweep(quats, &[8, 12, 16], &[128, 512]).unwrap();
+    assert_eq!(points.len(), 6);
+    assert_eq!(
+        points
+            .iter()
+            .map(|p| (p.block_size, p.qp))
+            .collect::<Vec<_>>(),
+        [
+            (128, 8),
+            (128, 12),
+            (128, 16),
+            (512, 8),
+            (512, 12),
+            (512, 16)
+        ]
+    );
+    for p in &points {
+        assert_eq!(p.bits_per_sample, p.bytes as f64 * 8.0 / 1000.0);
+        assert!(p.rmse <= p.max);
+    }
+    // a coarser quantizer saves bits and costs accuracy
+    for pair in points
+        .windows(2)
+        .filter(|w| w[0].block_size == w[1].block_size)
+    {
+        assert!(pair[0].bytes > pair[1].bytes);
+        assert!(pair[0].rmse < pair[1].rmse);
+    }
+    assert_eq!(points[4], sweep::encode_point(quats, 12, 512).unwrap());
+
+    let mut csv = vec![];
+    sweep::write_csv(&mut csv, &points[..1]).unwrap();
+    let text = String::from_utf8(csv).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next(),
+        Some("qp,block_size,bytes,bits_per_sample,rmse_deg,max_deg")
+    );
+    let p = points[0];
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(&format!("8,128,{},{:.4},", p.bytes, p.bits_per_sample)));
+}
+
+#[test]
+fn sweep_errors() {
+    let quats = &common::input()[..1000];
+    assert_eq!(
+        sweep::sweep(quats, &[14, 32], &[256]),
+        Err(Error::InvalidQp { qp: 32 })
+    );
+    assert_eq!(
+        sweep::sweep(quats, &[], &[256]),
+        Ok(Vec::<SweepPoint>::new())
+    );
+
+    // an empty stream is an empty file
+    let p = sweep::encode_point(&[], 14, 256).unwrap();
+    assert_eq!((p.bits_per_sample, p.rmse), (p.bytes as f64 * 8.0, 0.0));
+}
@@ -0,0 +1,72 @@
+use ebin::{
+    esplog::{Log, TimeMark},
+    metadata::{self, Metadata, Value},
+    quat::Quat,
+};
+
+// Sample timestamps spread over the time blocks of a log.
+
+fn log(quats: usize, accels: usize, time: &[(u32, usize, usize)]) -> Log {
+    Log {
+        quats: vec![Quat::default(); quats],
+        accels: vec![[0, 0, 2048]; accels],
+        time: time
+            .iter()
+            .map(|&(dt_us, quats, accels)| TimeMark {
+                dt_us,
+                quats,
+                accels,
+            })
+            .collect(),
+        ..Log::default()
+    }
+}
+
+#[test]
+fn spread_over_time_blocks() {
+    let mut l = log(12, 3, &[(4000, 4, 1), (4000, 8, 2)]);
+    l.time_offset_us = Some(-500);
+    let t = l.timeline().unwrap();
+
+    // the last sample of a span lands on its time block
+    assert_eq!(t.quats.us[..4], [500, 1500, 2500, 3500]);
+    assert_eq!(t.quats.us[7], 7500);
+    // the samples after the last block continue at the mean rate
+    assert_eq!(t.quats.us[8..], [8500, 9500, 10500, 11500]);
+    assert_eq!(t.quats.sample_rate, 1000.0);
+    assert_eq!(t.quats.jitter_us, 0.0);
+
+    assert_eq!(t.accels.us, [3500, 7500, 11500]);
+    assert_eq!(t.accels.sample_rate, 250.0);
+}
+
+#[test]
+fn jitter() {
+    // periods of 900 and 1100 us over equally long spans
+    let t = log(20, 0, &[(9000, 10, 0), (11000, 20, 0)])
+        .timeline()
+        .unwrap();
+    assert_eq!(t.quats.sample_rate, 1000.0);
+    assert!((t.quats.jitter_us - 100.0).abs() < 1e-9);
+    assert!(t.accels.us.is_empty());
+}
+
+#[test]
+fn without_time_blocks() {
+    // nothing to go by
+    let mut l = log(5, 2, &[]);
+    assert_eq!(l.timeline(), None);
+
+    // the nominal rate from the metadata, accel samples follow the gyro
+    let mut m = Metadata::new();
+    m.set(metadata::SAMPLE_RATE, Value::Int(500));
+    l.metadata = m;
+    let t = l.timeline().unwrap();
+    assert_eq!(t.quats.us, [2000, 4000, 6000, 8000, 10000]);
+    assert_eq!(t.accels.us, [2000, 4000]);
+    assert_eq!(t.quats.jitter_us, 0.0);
+
+    // an empty log has an empty timeline
+    let t = log(0, 0, &[]).timeline().unwrap();
+    assert!(t.quats.us.is_empty() && t.accels.us.is_empty());
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use std::path::Path;
+
+use ebin::{
+    esplog::{self, Writer},
+    fix32::Precision,
+    quat::{Fix, Quat},
+};
+
+// Error of sin, cos and atan2 at every precision level against f64, the
+// bounds are in ulp (2^-27). Low is the original approximation.
+
+const ULP: f64 = 1.0 / (1u64 << 27) as f64;
+
+fn bound(precision: Precision) -> (f64, f64) {
+    // (sin/cos, atan2)
+    match precision {
+        Precision::Low => (54_000.0, 114_000.0),
+        Precision::Medium => (2.5, 2.5),
+        Precision::High => (0.6, 0.6),
+    }
+}
+
+fn angles() -> Vec<Fix> {
+    (i32::MIN..=i32::MAX)
+        .step_by(4_999)
+        .map(Fix::from_raw)
+        .collect()
+}
+
+// points on circles of several radii plus the axes
+fn points() -> Vec<(Fix, Fix)> {
+    let mut p = vec![];
+    for r in [1e-4, 0.01, 0.5, 1.0, 3.0, 15.0] {
+        for i in 0..20_000 {
+            let a = i as f64 / 20_000.0 * std::f64::consts::TAU;
+            let f = |x: f64| Fix::from_raw((x / ULP).round() as i32);
+            p.push((f(r * a.sin()), f(r * a.cos())));
+        }
+    }
+    p
+}
+
+fn max_err(samples: impl Iterator<Item = (f64, f64)>) -> f64 {
+    samples
+        .map(|(got, exact)| (got - exact).abs() / ULP)
+        .fold(0.0, f64::max)
+}
+
+// pi and -pi are the same direction
+fn angle_err(samples: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let tau = std::f64::consts::TAU;
+    max_err(samples.map(|(got, exact)| {
+        let d = (got - exact).rem_euclid(tau);
+        (d.min(tau - d), 0.0)
+    }))
+}
+
+fn f(x: Fix) -> f64 {
+    x.to_raw() as f64 * ULP
+}
+
+#[test]
+fn sin_cos() {
+    let xs = angles();
+    for p in [Precision::Low, Precision::Medium, Precision::High] {
+        let sin = max_err(xs.iter().map(|&x| (f(x.sin_with(p)), f(x).sin())));
+        // Low computes cos(x) as sin(x + pi/2), which wraps at the top of the range
+        let cos = max_err(
+            xs.iter()
+                .filter(|&&x| p != Precision::Low || f(x) < 16.0 - std::f64::consts::FRAC_PI_2)
+                .map(|&x| (f(x.cos_with(p)), f(x).cos())),
+        );
+        assert!(sin <= bound(p).0, "{:?}: sin error {} ulp", p, sin);
+        assert!(cos <= bound(p).0, "{:?}: cos error {} ulp", p, cos);
+    }
+}
+
+#[test]
+fn atan2() {
+    let ps = points();
+    for p in [Precision::Low, Precision::Medium, Precision::High] {
+        // Low has the wrong sign for y < 0 < x, see low_atan2_is_the_original
+        let err = angle_err(
+            ps.iter()
+                .filter(|(y, x)| x.to_raw() != 0 || y.to_raw() != 0)
+                .filter(|(y, x)| p != Precision::Low || y.to_raw() >= 0 || x.to_raw() <= 0)
+                .map(|&(y, x)| (f(y.atan2_with(x, p)), f(y).atan2(f(x)))),
+        );
+        assert!(err <= bound(p).1, "{:?}: atan2 error {} ulp", p, err);
+    }
+}
+
+#[test]
+fn default_is_the_original() {
+    for x in angles().into_iter().step_by(97) {
+        assert_eq!(x.sin_with(Precision::default()), x.sin());
+        // cos overflows near the top of the range in debug builds
+        if f(x) < 16.0 - std::f64::consts::FRAC_PI_2 {
+            assert_eq!(x.cos_with(Precision::default()), x.cos());
+        }
+    }
+}
+
+fn grid() -> Vec<i32> {
+    let mut v = vec![0, 1, -1, 3, -3, i32::MAX, -i32::MAX];
+    for k in 2..31 {
+        let m = (1 << k) + (k * 7919) % (1 << k);
+        v.extend([m, -m]);
+    }
+    v
+}
+
+// testdata/atan2_low.bin was recorded with the atan2 of the original
+// encoder on this grid, row by row in y, skipping (0, 0)
+#[test]
+fn low_atan2_is_the_original() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/atan2_low.bin");
+    let expected = std::fs::read(path).unwrap();
+    let mut got = vec![];
+    for &y in &grid() {
+        for &x in &grid() {
+            if x != 0 || y != 0 {
+                let a = Fix::from_raw(y).atan2_with(Fix::from_raw(x), Precision::Low);
+                got.extend_from_slice(&a.to_raw().to_le_bytes());
+            }
+        }
+    }
+    assert!(got == expected);
+    // the quadrant the original gets wrong
+    let (y, x) = (Fix::from_float(-0.5), Fix::from_float(0.5));
+    assert!(y.atan2(x).to_float() > 0.78);
+}
+
+// angle between orientations in rad, computed in f64 from the raw values
+fn quat_err(a: &Quat, b: &Quat) -> f64 {
+    let unit = |q: &Quat| {
+        let c = [q.w, q.x, q.y, q.z].map(f);
+        let n = c.iter().map(|x| x * x).sum::<f64>().sqrt();
+        c.map(|x| x / n)
+    };
+    let (a, b) = (unit(a), unit(b));
+    let s = if (0..4).map(|i| a[i] * b[i]).sum::<f64>() < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let chord = (0..4)
+        .map(|i| (a[i] - s * b[i]).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    4.0 * (chord / 2.0).asin()
+}
+
+// gyro revision 0x02 removes the approximation error floor of 0x01
+#[test]
+fn gyro_revision_error_floor() {
+    let input = common::input();
+    let max_err = |revision: u8| {
+        let mut w = Writer::new(4).unwrap();
+        w.set_gyro_revision(revision).unwrap();
+        for chunk in input.chunks(512) {
+            w.gyro_data(chunk).unwrap();
+        }
+        let log = esplog::decode(&w.finish()).unwrap();
+        input
+            .iter()
+            .zip(&log.quats)
+            .map(|(a, b)| quat_err(a, b))
+            .fold(0.0, f64::max)
+    };
+    assert!(max_err(0x01) > 5e-6);
+    assert!(max_err(0x02) < 5e-7);
+    assert!(Writer::new(4).unwrap().set_gyro_revision(0x03).is_err());
+}
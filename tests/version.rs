@@ -0,0 +1,111 @@
+mod common;
+
+use common::{encode, input};
+use ebin::{
+    esplog::{self, Error, Writer, BLK_GYRO_SETUP, BLK_KEYFRAME, BLK_METADATA, MAGIC},
+    version,
+};
+
+// Format version and gyro revision handling of the reader.
+
+// Keyframes came with format version 1, version 0 files are read without
+// them and a keyframe in one is an unknown block.
+#[test]
+fn no_keyframes_in_version_0() {
+    assert!(!version::format_version(b'0')
+        .unwrap()
+        .blocks
+        .contains(&BLK_KEYFRAME));
+
+    let mut w = Writer::new(14).unwrap();
+    for chunk in input().chunks(100) {
+        w.gyro_data(chunk).unwrap();
+        w.time(100_000);
+    }
+    let mut old = w.finish();
+    let log = esplog::decode(&old).unwrap();
+    old[MAGIC.len()] = b'0';
+    let old_log = esplog::decode(&old).unwrap();
+    assert_eq!(old_log.format_version, b'0');
+    assert!(old_log.quats == log.quats);
+    assert!(esplog::decode_parallel(&old, 3).unwrap().quats == log.quats);
+
+    let mut buf = encode(&input());
+    buf[MAGIC.len()] = b'0';
+    let keyframe = esplog::HEADER_SIZE;
+    assert_eq!(buf[keyframe], BLK_KEYFRAME);
+    assert_eq!(
+        esplog::decode(&buf),
+        Err(Error::UnknownBlock {
+            id: BLK_KEYFRAME,
+            pos: keyframe
+        })
+    );
+    assert_eq!(esplog::decode_parallel(&buf, 3), esplog::decode(&buf));
+}
+
+#[test]
+fn registry() {
+    let caps = version::capabilities();
+    assert_eq!(caps.latest_format_version, version::LATEST_FORMAT_VERSION);
+    assert!(caps.format_versions.iter().any(|v| v.version == b'0'));
+    assert!(version::gyro_revision(0x01).is_some());
+    assert!(version::gyro_revision(0x02).is_some());
+    assert!(version::gyro_revision(0x00).is_none());
+    assert!(version::format_version(b'9').is_none());
+
+    // every version keeps the blocks of the previous one
+    for pair in caps.format_versions.windows(2) {
+        assert!(pair[0].blocks.iter().all(|b| pair[1].blocks.contains(b)));
+    }
+    assert!(!version::format_version(b'0')
+        .unwrap()
+        .blocks
+        .contains(&BLK_METADATA));
+}
+
+#[test]
+fn writer_produces_latest_version() {
+    let buf = encode(&input()[..200]);
+    assert_eq!(&buf[..MAGIC.len()], MAGIC);
+    assert_eq!(buf[MAGIC.len()], version::LATEST_FORMAT_VERSION);
+    let log = esplog::decode(&buf).unwrap();
+    assert_eq!(log.format_version, version::LATEST_FORMAT_VERSION);
+}
+
+#[test]
+fn unsupported_version_and_revision() {
+    let buf = encode(&input()[..200]);
+
+    let mut future = buf.clone();
+    future[MAGIC.len()] = b'9';
+    assert_eq!(
+        esplog::decode(&future),
+        Err(Error::UnsupportedVersion { version: b'9' })
+    );
+
+    let setup = esplog::scan(&buf)
+        .unwrap()
+        .iter()
+        .find(|b| b.id == BLK_GYRO_SETUP)
+        .unwrap()
+        .pos;
+    let mut revision = buf.clone();
+    revision[setup + 1] = 0x7f;
+    assert_eq!(
+        esplog::decode(&revision),
+        Err(Error::UnsupportedRevision { revision: 0x7f })
+    );
+
+    // blocks newer than the file's version are unknown to it
+    let mut meta = buf[..esplog::HEADER_SIZE].to_vec();
+    meta[MAGIC.len()] = b'0';
+    meta.extend_from_slice(&[BLK_METADATA, 0]);
+    assert_eq!(
+        esplog::decode(&meta),
+        Err(Error::UnknownBlock {
+            id: BLK_METADATA,
+            pos: esplog::HEADER_SIZE
+        })
+    );
+}